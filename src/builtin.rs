@@ -0,0 +1,96 @@
+use std::f64::consts;
+
+/// A math function every session has, e.g. `sqrt(x)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Builtin {
+    pub name: &'static str,
+    pub arity: usize,
+    /// LLVM intrinsic, or C math library function when LLVM has none.
+    pub symbol: &'static str,
+}
+
+const BUILTINS: &[Builtin] = &[
+    Builtin::new("sqrt", 1, "llvm.sqrt.f64"),
+    Builtin::new("sin", 1, "llvm.sin.f64"),
+    Builtin::new("cos", 1, "llvm.cos.f64"),
+    Builtin::new("tan", 1, "tan"),
+    Builtin::new("log", 1, "llvm.log10.f64"),
+    Builtin::new("ln", 1, "llvm.log.f64"),
+    Builtin::new("exp", 1, "llvm.exp.f64"),
+    Builtin::new("abs", 1, "llvm.fabs.f64"),
+    Builtin::new("floor", 1, "llvm.floor.f64"),
+    Builtin::new("ceil", 1, "llvm.ceil.f64"),
+    Builtin::new("round", 1, "llvm.round.f64"),
+    Builtin::new("min", 2, "llvm.minnum.f64"),
+    Builtin::new("max", 2, "llvm.maxnum.f64"),
+];
+
+/// A read-only value every session has, e.g. `pi`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Constant {
+    pub name: &'static str,
+    pub value: f64,
+}
+
+const CONSTANTS: &[Constant] = &[
+    Constant::new("pi", consts::PI),
+    Constant::new("e", consts::E),
+    Constant::new("tau", consts::TAU),
+    // golden ratio
+    Constant::new("phi", 1.618_033_988_749_895),
+    Constant::new("inf", f64::INFINITY),
+    Constant::new("nan", f64::NAN),
+];
+
+impl Builtin {
+    const fn new(name: &'static str, arity: usize, symbol: &'static str) -> Self {
+        Builtin {
+            name,
+            arity,
+            symbol,
+        }
+    }
+
+    pub fn lookup(name: &str) -> Option<&'static Builtin> {
+        BUILTINS.iter().find(|builtin| builtin.name == name)
+    }
+
+    pub fn all() -> &'static [Builtin] {
+        BUILTINS
+    }
+}
+
+impl Constant {
+    const fn new(name: &'static str, value: f64) -> Self {
+        Constant { name, value }
+    }
+
+    pub fn lookup(name: &str) -> Option<&'static Constant> {
+        CONSTANTS.iter().find(|constant| constant.name == name)
+    }
+
+    pub fn all() -> &'static [Constant] {
+        CONSTANTS
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_lookup() {
+        let max = Builtin::lookup("max").unwrap();
+
+        assert_eq!(2, max.arity);
+        assert_eq!("llvm.maxnum.f64", max.symbol);
+        assert_eq!(None, Builtin::lookup("luas"));
+    }
+
+    #[test]
+    fn test_constant_lookup() {
+        assert_eq!(consts::TAU, Constant::lookup("tau").unwrap().value);
+        assert!(Constant::lookup("nan").unwrap().value.is_nan());
+        assert_eq!(None, Constant::lookup("pajak"));
+    }
+}
@@ -0,0 +1,247 @@
+use std::fmt::Write;
+
+use crate::error::HitungError;
+use crate::keyword::Dialect;
+use crate::message::{self, Language};
+use crate::span::Span;
+
+/// An error message that can be shown against the source it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub span: Span,
+    pub label: Option<String>,
+    pub help: Option<String>,
+    pub language: Language,
+}
+
+impl Diagnostic {
+    pub fn new(message: impl Into<String>, span: Span) -> Self {
+        Diagnostic {
+            message: message.into(),
+            span,
+            label: None,
+            help: None,
+            language: Language::default(),
+        }
+    }
+
+    pub fn with_label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help = Some(help.into());
+        self
+    }
+
+    /// Diagnostic for `error` in `language`, with keywords spelled the way
+    /// `dialect` writes them.
+    pub fn from_error(error: &HitungError, language: Language, dialect: Dialect) -> Self {
+        let dialect = dialect.for_language(language);
+        let text = message::text(error.kind(), language);
+        let mut arguments = error.arguments(dialect);
+        arguments.extend(dialect.placeholders());
+
+        let help = match error {
+            HitungError::ExpectedToken { expected, .. } => message::syntax_help(expected, language),
+            _ => text.help,
+        };
+
+        Diagnostic {
+            message: message::fill(text.message, &arguments),
+            span: error.span(),
+            label: text.label.map(|label| message::fill(label, &arguments)),
+            help: help.map(|help| message::fill(help, &arguments)),
+            language,
+        }
+    }
+
+    /// Render the diagnostic rustc style, `name` is shown as the file name.
+    ///
+    /// ```text
+    /// error: variable `b` is not declared
+    ///  --> <repl>:1:5
+    ///   |
+    /// 1 | a + b
+    ///   |     ^ not declared
+    ///   |
+    ///   = help: assign a value first, e.g. `b = 0`
+    /// ```
+    pub fn render(&self, name: &str, source: &str) -> String {
+        let line_number = self.span.line.max(1);
+        let line = source.lines().nth(line_number - 1).unwrap_or("");
+        let gutter = " ".repeat(line_number.to_string().len());
+
+        // Everything before the span keeps its tabs so the caret lines up.
+        let padding: String = line
+            .chars()
+            .take(self.span.column.saturating_sub(1))
+            .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+            .collect();
+        let width = source
+            .get(self.span.offset..self.span.end())
+            .and_then(|text| text.lines().next())
+            .map(|text| text.chars().count())
+            .unwrap_or(0)
+            .max(1);
+
+        let (error, help) = message::heading(self.language);
+
+        let mut output = String::new();
+        let _ = writeln!(output, "{}: {}", error, self.message);
+        let _ = writeln!(
+            output,
+            "{}--> {}:{}:{}",
+            gutter, name, line_number, self.span.column
+        );
+        let _ = writeln!(output, "{} |", gutter);
+        let _ = writeln!(output, "{} | {}", line_number, line);
+        let _ = write!(output, "{} | {}{}", gutter, padding, "^".repeat(width));
+        if let Some(label) = &self.label {
+            let _ = write!(output, " {}", label);
+        }
+        output.push('\n');
+        if let Some(text) = &self.help {
+            let _ = writeln!(output, "{} |", gutter);
+            let _ = writeln!(output, "{} = {}: {}", gutter, help, text);
+        }
+
+        output
+    }
+}
+
+impl From<&HitungError> for Diagnostic {
+    fn from(error: &HitungError) -> Self {
+        Diagnostic::from_error(error, Language::default(), Dialect::default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::token::Token;
+
+    #[test]
+    fn test_render() {
+        let error = HitungError::UndeclaredVariable {
+            name: "b".to_string(),
+            span: Span::new(4, 1, 1, 5),
+        };
+
+        let actual = Diagnostic::from(&error).render("<repl>", "a + b");
+
+        let expected = "\
+error: variable `b` is not declared
+ --> <repl>:1:5
+  |
+1 | a + b
+  |     ^ not declared
+  |
+  = help: assign a value first, e.g. `b = 0`
+";
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_render_second_line() {
+        let diagnostic = Diagnostic::new("unexpected token `*`", Span::new(6, 2, 2, 1))
+            .with_label("unexpected here");
+
+        let actual = diagnostic.render("rumus.htg", "a = 1\n** 2");
+
+        let expected = "\
+error: unexpected token `*`
+ --> rumus.htg:2:1
+  |
+2 | ** 2
+  | ^^ unexpected here
+";
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_render_indonesian_keywords() {
+        let error = HitungError::ExpectedToken {
+            expected: Token::Then,
+            found: Token::from(1),
+            span: Span::new(11, 1, 1, 12),
+        };
+
+        let source = "jika a > 0 1 selain 0";
+        let actual = Diagnostic::from_error(&error, Language::English, Dialect::Indonesian)
+            .render("<repl>", source);
+
+        let expected = "\
+error: expected `maka`, found `1`
+ --> <repl>:1:12
+  |
+1 | jika a > 0 1 selain 0
+  |            ^ expected `maka` here
+  |
+  = help: a conditional looks like `jika condition maka value selain value`
+";
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_render_indonesian() {
+        let error = HitungError::UndeclaredFunction {
+            name: "luas".to_string(),
+            span: Span::new(0, 4, 1, 1),
+        };
+
+        let diagnostic = Diagnostic::from_error(&error, Language::Indonesian, Dialect::Both);
+        let actual = diagnostic.render("<repl>", "luas(3, 4)");
+
+        let expected = "\
+galat: fungsi `luas` belum didefinisikan
+ --> <repl>:1:1
+  |
+1 | luas(3, 4)
+  | ^^^^ belum didefinisikan
+  |
+  = bantuan: definisikan terlebih dahulu, misalnya `fungsi luas(x) = x`
+";
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_render_both_keywords() {
+        let error = HitungError::ExpectedToken {
+            expected: Token::Then,
+            found: Token::from(1),
+            span: Span::new(11, 1, 1, 12),
+        };
+        let source = "jika 1 < 2 1 selain 0";
+
+        let actual = Diagnostic::from_error(&error, Language::Indonesian, Dialect::Both)
+            .render("<repl>", source);
+        let expected = "\
+galat: seharusnya `maka`, bukan `1`
+ --> <repl>:1:12
+  |
+1 | jika 1 < 2 1 selain 0
+  |            ^ seharusnya `maka` di sini
+  |
+  = bantuan: percabangan ditulis `jika kondisi maka nilai selain nilai`
+";
+        assert_eq!(expected, actual);
+
+        let actual = Diagnostic::from_error(&error, Language::English, Dialect::Both)
+            .render("<repl>", source);
+        assert!(actual.starts_with("error: expected `then`, found `1`"));
+        assert!(actual
+            .ends_with("= help: a conditional looks like `if condition then value else value`\n"));
+
+        let error = HitungError::ExpectedToken {
+            expected: Token::Do,
+            found: Token::EOF,
+            span: Span::new(11, 0, 1, 12),
+        };
+        let actual = Diagnostic::from_error(&error, Language::English, Dialect::Both)
+            .render("<repl>", "while x > 0");
+        assert!(actual.contains("a loop looks like `while condition do body`"));
+    }
+}
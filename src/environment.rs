@@ -0,0 +1,84 @@
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// Variables of a session.
+///
+/// Every variable lives in its own heap allocation, so its address stays the
+/// same for the whole session and JIT compiled code can load and store
+/// through it directly, no matter which module the code was compiled into.
+/// Slots are reference counted so compiled code can keep the ones it uses
+/// alive after the session is gone.
+#[derive(Debug, Default)]
+pub struct Environment {
+    slots: HashMap<String, Rc<Cell<f64>>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Environment::default()
+    }
+
+    pub fn get(&self, name: &str) -> Option<f64> {
+        self.slots.get(name).map(|slot| slot.get())
+    }
+
+    /// Address of the slot for `name`, if it has been declared.
+    pub fn address(&self, name: &str) -> Option<usize> {
+        self.slots.get(name).map(|slot| slot.as_ptr() as usize)
+    }
+
+    /// Address of the slot for `name`, creating it with `0` when needed.
+    pub fn declare(&mut self, name: &str) -> usize {
+        self.slots
+            .entry(name.to_string())
+            .or_insert_with(|| Rc::new(Cell::new(0.0)))
+            .as_ptr() as usize
+    }
+
+    /// Every slot, for keeping them alive alongside compiled code.
+    pub fn slots(&self) -> Vec<Rc<Cell<f64>>> {
+        self.slots.values().cloned().collect()
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.slots.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// Forget every variable that is not in `names`.
+    pub fn retain(&mut self, names: &[String]) {
+        self.slots.retain(|name, _| names.contains(name));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_declare_keeps_address() {
+        let mut environment = Environment::new();
+
+        let address = environment.declare("a");
+        environment.declare("b");
+
+        assert_eq!(address, environment.declare("a"));
+        assert_eq!(Some(0.0), environment.get("a"));
+        assert_eq!(vec!["a".to_string(), "b".to_string()], environment.names());
+    }
+
+    #[test]
+    fn test_retain() {
+        let mut environment = Environment::new();
+        environment.declare("a");
+        let before = environment.names();
+
+        environment.declare("b");
+        environment.retain(&before);
+
+        assert_eq!(None, environment.get("b"));
+        assert_eq!(Some(0.0), environment.get("a"));
+    }
+}
@@ -0,0 +1,273 @@
+use std::error::Error;
+use std::fmt;
+
+use crate::keyword::Dialect;
+use crate::message::{self, Language};
+use crate::span::Span;
+use crate::token::Token;
+
+pub type Result<T> = std::result::Result<T, HitungError>;
+
+/// Stable, payload free identifier of a [`HitungError`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    IllegalCharacter,
+    UnterminatedComment,
+    MalformedNumber,
+    UnexpectedToken,
+    UnexpectedEof,
+    UnmatchedParen,
+    ExpectedToken,
+    ReservedKeyword,
+    UndeclaredVariable,
+    UndeclaredFunction,
+    ArityMismatch,
+    DuplicateParameter,
+    DuplicateFunction,
+    InvalidAssignment,
+    ConstantAssignment,
+    UnsupportedOperator,
+    ZeroStep,
+    RecursionLimit,
+    Llvm,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HitungError {
+    IllegalCharacter {
+        character: char,
+        span: Span,
+    },
+    UnterminatedComment {
+        span: Span,
+    },
+    MalformedNumber {
+        literal: String,
+        span: Span,
+    },
+    UnexpectedToken {
+        token: Token,
+        span: Span,
+    },
+    UnexpectedEof {
+        span: Span,
+    },
+    UnmatchedParen {
+        span: Span,
+    },
+    ExpectedToken {
+        expected: Token,
+        found: Token,
+        span: Span,
+    },
+    ReservedKeyword {
+        // as written in the source
+        keyword: String,
+        span: Span,
+    },
+    UndeclaredVariable {
+        name: String,
+        span: Span,
+    },
+    UndeclaredFunction {
+        name: String,
+        span: Span,
+    },
+    ArityMismatch {
+        name: String,
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
+    DuplicateParameter {
+        name: String,
+        span: Span,
+    },
+    DuplicateFunction {
+        name: String,
+        span: Span,
+    },
+    InvalidAssignment {
+        span: Span,
+    },
+    ConstantAssignment {
+        name: String,
+        span: Span,
+    },
+    UnsupportedOperator {
+        operator: Token,
+        span: Span,
+    },
+    ZeroStep {
+        span: Span,
+    },
+    RecursionLimit {
+        limit: u64,
+        span: Span,
+    },
+    Llvm {
+        message: String,
+        span: Span,
+    },
+}
+
+impl HitungError {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            HitungError::IllegalCharacter { .. } => ErrorKind::IllegalCharacter,
+            HitungError::UnterminatedComment { .. } => ErrorKind::UnterminatedComment,
+            HitungError::MalformedNumber { .. } => ErrorKind::MalformedNumber,
+            HitungError::UnexpectedToken { .. } => ErrorKind::UnexpectedToken,
+            HitungError::UnexpectedEof { .. } => ErrorKind::UnexpectedEof,
+            HitungError::UnmatchedParen { .. } => ErrorKind::UnmatchedParen,
+            HitungError::ExpectedToken { .. } => ErrorKind::ExpectedToken,
+            HitungError::ReservedKeyword { .. } => ErrorKind::ReservedKeyword,
+            HitungError::UndeclaredVariable { .. } => ErrorKind::UndeclaredVariable,
+            HitungError::UndeclaredFunction { .. } => ErrorKind::UndeclaredFunction,
+            HitungError::ArityMismatch { .. } => ErrorKind::ArityMismatch,
+            HitungError::DuplicateParameter { .. } => ErrorKind::DuplicateParameter,
+            HitungError::DuplicateFunction { .. } => ErrorKind::DuplicateFunction,
+            HitungError::InvalidAssignment { .. } => ErrorKind::InvalidAssignment,
+            HitungError::ConstantAssignment { .. } => ErrorKind::ConstantAssignment,
+            HitungError::UnsupportedOperator { .. } => ErrorKind::UnsupportedOperator,
+            HitungError::ZeroStep { .. } => ErrorKind::ZeroStep,
+            HitungError::RecursionLimit { .. } => ErrorKind::RecursionLimit,
+            HitungError::Llvm { .. } => ErrorKind::Llvm,
+        }
+    }
+
+    pub fn span(&self) -> Span {
+        match self {
+            HitungError::IllegalCharacter { span, .. }
+            | HitungError::UnterminatedComment { span }
+            | HitungError::MalformedNumber { span, .. }
+            | HitungError::UnexpectedToken { span, .. }
+            | HitungError::UnexpectedEof { span }
+            | HitungError::UnmatchedParen { span }
+            | HitungError::ExpectedToken { span, .. }
+            | HitungError::ReservedKeyword { span, .. }
+            | HitungError::UndeclaredVariable { span, .. }
+            | HitungError::UndeclaredFunction { span, .. }
+            | HitungError::ArityMismatch { span, .. }
+            | HitungError::DuplicateParameter { span, .. }
+            | HitungError::DuplicateFunction { span, .. }
+            | HitungError::InvalidAssignment { span }
+            | HitungError::ConstantAssignment { span, .. }
+            | HitungError::UnsupportedOperator { span, .. }
+            | HitungError::ZeroStep { span }
+            | HitungError::RecursionLimit { span, .. }
+            | HitungError::Llvm { span, .. } => *span,
+        }
+    }
+
+    /// Values for the placeholders of the catalog texts, keywords are
+    /// spelled the way `dialect` writes them.
+    pub fn arguments(&self, dialect: Dialect) -> Vec<(&'static str, String)> {
+        match self {
+            HitungError::IllegalCharacter { character, .. } => {
+                vec![("character", character.to_string())]
+            }
+            HitungError::MalformedNumber { literal, .. } => vec![("literal", literal.clone())],
+            HitungError::UnexpectedToken { token, .. } => vec![("token", dialect.spell(token))],
+            HitungError::ExpectedToken {
+                expected, found, ..
+            } => vec![
+                ("expected", dialect.spell(expected)),
+                ("found", dialect.spell(found)),
+            ],
+            HitungError::ReservedKeyword { keyword, .. } => vec![("keyword", keyword.clone())],
+            HitungError::UndeclaredVariable { name, .. }
+            | HitungError::UndeclaredFunction { name, .. }
+            | HitungError::DuplicateParameter { name, .. }
+            | HitungError::DuplicateFunction { name, .. }
+            | HitungError::ConstantAssignment { name, .. } => vec![("name", name.clone())],
+            HitungError::ArityMismatch {
+                name,
+                expected,
+                found,
+                ..
+            } => vec![
+                ("name", name.clone()),
+                ("expected", expected.to_string()),
+                ("found", found.to_string()),
+            ],
+            HitungError::UnsupportedOperator { operator, .. } => {
+                vec![("operator", dialect.spell(operator))]
+            }
+            HitungError::RecursionLimit { limit, .. } => vec![("limit", limit.to_string())],
+            HitungError::Llvm { message, .. } => vec![("message", message.clone())],
+            HitungError::UnterminatedComment { .. }
+            | HitungError::UnexpectedEof { .. }
+            | HitungError::UnmatchedParen { .. }
+            | HitungError::InvalidAssignment { .. }
+            | HitungError::ZeroStep { .. } => Vec::new(),
+        }
+    }
+
+    /// The message from the catalog in `language`.
+    pub fn message(&self, language: Language, dialect: Dialect) -> String {
+        let text = message::text(self.kind(), language);
+        message::fill(
+            text.message,
+            &self.arguments(dialect.for_language(language)),
+        )
+    }
+}
+
+impl fmt::Display for HitungError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            self.message(Language::default(), Dialect::default())
+        )
+    }
+}
+
+impl Error for HitungError {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_kind_and_span() {
+        let span = Span::new(4, 1, 1, 5);
+        let error = HitungError::UndeclaredVariable {
+            name: "b".to_string(),
+            span,
+        };
+
+        assert_eq!(ErrorKind::UndeclaredVariable, error.kind());
+        assert_eq!(span, error.span());
+    }
+
+    #[test]
+    fn test_display() {
+        let error = HitungError::UnexpectedToken {
+            token: Token::Mul,
+            span: Span::default(),
+        };
+
+        assert_eq!("unexpected token `*`", error.to_string());
+    }
+
+    #[test]
+    fn test_message_spells_keywords() {
+        let error = HitungError::ExpectedToken {
+            expected: Token::Then,
+            found: Token::Else,
+            span: Span::default(),
+        };
+
+        assert_eq!("expected `then`, found `else`", error.to_string());
+        assert_eq!(
+            "expected `maka`, found `selain`",
+            error.message(Language::English, Dialect::Indonesian)
+        );
+        assert_eq!(
+            "seharusnya `maka`, bukan `selain`",
+            error.message(Language::Indonesian, Dialect::Indonesian)
+        );
+    }
+}
@@ -1,13 +1,65 @@
+use crate::span::{Span, Spanned};
 use crate::token::Token;
 
 #[derive(Debug, PartialEq)]
 pub enum Expression {
     Num(f64),
-    Unary(Token, Box<Expression>),
-    Binary(Box<Expression>, Token, Box<Expression>),
-    Paren(Box<Expression>),
+    Unary(Token, Box<Spanned<Expression>>),
+    Binary(Box<Spanned<Expression>>, Token, Box<Spanned<Expression>>),
+    Paren(Box<Spanned<Expression>>),
     Variable(String),
-    Conditional(Box<Expression>, Box<Expression>, Box<Expression>),
+    Call(String, Vec<Spanned<Expression>>),
+    Conditional(
+        Box<Spanned<Expression>>,
+        Box<Spanned<Expression>>,
+        Box<Spanned<Expression>>,
+    ),
+    While(Box<Spanned<Expression>>, Box<Spanned<Expression>>),
+    /// `untuk name dari start sampai end [langkah step] lakukan body`
+    For(
+        String,
+        Box<Spanned<Expression>>,
+        Box<Spanned<Expression>>,
+        Option<Box<Spanned<Expression>>>,
+        Box<Spanned<Expression>>,
+    ),
+}
+
+/// `fungsi name(params) = body`
+#[derive(Debug, PartialEq)]
+pub struct Function {
+    pub name: Spanned<String>,
+    pub params: Vec<Spanned<String>>,
+    pub body: Spanned<Expression>,
+}
+
+/// A whole input: statements separated by newlines or `;`.
+///
+/// Function definitions are kept apart from the other statements, they can
+/// be called from anywhere in the program no matter where they are defined.
+#[derive(Debug, PartialEq, Default)]
+pub struct Program {
+    pub functions: Vec<Spanned<Function>>,
+    pub statements: Vec<Spanned<Expression>>,
+}
+
+impl Program {
+    /// Span from the first to the last statement or definition.
+    pub fn span(&self) -> Option<Span> {
+        let spans = self
+            .functions
+            .iter()
+            .map(|function| function.span)
+            .chain(self.statements.iter().map(|statement| statement.span));
+
+        spans.reduce(|first, span| {
+            if span.offset < first.offset {
+                span.to(first)
+            } else {
+                first.to(span)
+            }
+        })
+    }
 }
 
 impl From<isize> for Expression {
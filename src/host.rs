@@ -0,0 +1,112 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+
+/// Rust closure that can be registered with
+/// [`Compiler::register_fn`](crate::jit::Compiler::register_fn), implemented
+/// for `Fn(f64, ..) -> f64` with up to four arguments.
+///
+/// Compiled code cannot call a closure directly, it calls the trampoline
+/// instead: an `extern "C"` function getting the address of the closure as
+/// its first argument followed by the `f64` arguments.
+pub trait HostFn<Args>: 'static {
+    const ARITY: usize;
+
+    fn trampoline() -> usize;
+}
+
+macro_rules! host_fn {
+    ($($arg:ident),*) => {
+        impl<F> HostFn<($(host_fn!(@f64 $arg),)*)> for F
+        where
+            F: Fn($(host_fn!(@f64 $arg)),*) -> f64 + 'static,
+        {
+            const ARITY: usize = <[&str]>::len(&[$(stringify!($arg)),*]);
+
+            fn trampoline() -> usize {
+                extern "C" fn call<F>(closure: usize, $($arg: f64),*) -> f64
+                where
+                    F: Fn($(host_fn!(@f64 $arg)),*) -> f64,
+                {
+                    let closure = unsafe { &*(closure as *const F) };
+                    // unwinding into the compiled code is undefined behaviour
+                    panic::catch_unwind(AssertUnwindSafe(|| closure($($arg),*)))
+                        .unwrap_or(f64::NAN)
+                }
+
+                let call: extern "C" fn(usize, $(host_fn!(@f64 $arg)),*) -> f64 = call::<F>;
+                call as usize
+            }
+        }
+    };
+    (@f64 $arg:ident) => { f64 };
+}
+
+host_fn!();
+host_fn!(a);
+host_fn!(a, b);
+host_fn!(a, b, c);
+host_fn!(a, b, c, d);
+
+/// A registered closure and how to call it.
+pub struct HostFunction {
+    pub arity: usize,
+    /// Name of the declaration in the compiled modules, unique for every
+    /// registration.
+    pub symbol: String,
+    pub trampoline: usize,
+    // boxed so the address handed to the compiled code stays put
+    closure: Box<dyn Any>,
+}
+
+impl HostFunction {
+    pub fn new<Args, F: HostFn<Args>>(symbol: String, closure: F) -> Self {
+        HostFunction {
+            arity: F::ARITY,
+            symbol,
+            trampoline: F::trampoline(),
+            closure: Box::new(closure),
+        }
+    }
+
+    /// Address of the closure, the first argument of the trampoline.
+    pub fn closure(&self) -> usize {
+        let closure: &dyn Any = &*self.closure;
+        closure as *const dyn Any as *const () as usize
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_trampoline() {
+        let rate = 0.1;
+        let function = HostFunction::new("host.pajak.0".to_string(), move |x: f64| x * rate);
+
+        let trampoline: extern "C" fn(usize, f64) -> f64 =
+            unsafe { std::mem::transmute(function.trampoline) };
+
+        assert_eq!(1, function.arity);
+        assert_eq!(5.0, trampoline(function.closure(), 50.0));
+    }
+
+    #[test]
+    fn test_arity() {
+        let none = HostFunction::new(String::new(), || 1.0);
+        let two = HostFunction::new(String::new(), |a: f64, b: f64| a + b);
+
+        assert_eq!(0, none.arity);
+        assert_eq!(2, two.arity);
+    }
+
+    #[test]
+    fn test_panic_is_nan() {
+        let function = HostFunction::new(String::new(), |_: f64| -> f64 { panic!("gagal") });
+
+        let trampoline: extern "C" fn(usize, f64) -> f64 =
+            unsafe { std::mem::transmute(function.trampoline) };
+
+        assert!(trampoline(function.closure(), 1.0).is_nan());
+    }
+}
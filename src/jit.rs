@@ -1,266 +1,2112 @@
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::fmt;
+use std::mem;
 use std::path::Path;
+use std::rc::Rc;
 
 use inkwell;
+use inkwell::attributes::{Attribute, AttributeLoc};
+use inkwell::basic_block::BasicBlock;
 use inkwell::builder::Builder;
 use inkwell::context::Context;
+use inkwell::execution_engine::{ExecutionEngine, JitFunction};
 use inkwell::module::Module;
-use inkwell::values::PointerValue;
+use inkwell::passes::PassManager;
+use inkwell::targets::{
+    CodeModel, InitializationConfig, RelocMode, Target, TargetMachine, TargetTriple,
+};
+use inkwell::values::{BasicValueEnum, FloatValue, FunctionValue, IntValue, PointerValue};
+use inkwell::AddressSpace;
 use inkwell::FloatPredicate;
+use inkwell::IntPredicate;
 use inkwell::OptimizationLevel;
 
-use crate::expression::Expression;
+use crate::builtin::{Builtin, Constant};
+use crate::environment::Environment;
+use crate::error::{HitungError, Result};
+use crate::expression::{Expression, Function, Program};
+use crate::host::{HostFn, HostFunction};
+use crate::keyword::Dialect;
 use crate::lexer::Lexer;
 use crate::parser::Parser;
+use crate::span::{Span, Spanned};
 use crate::token::Token;
 
 pub type FuncSign = unsafe extern "C" fn() -> f64;
+pub type FormulaSign = unsafe extern "C" fn(*const f64) -> f64;
+pub type BatchSign = unsafe extern "C" fn(*const *const f64, *mut f64, u64);
+
+/// How deep user functions may call each other before evaluation stops.
+pub const DEFAULT_RECURSION_LIMIT: u64 = 10_000;
+
+/// A function defined with `fungsi`.
+#[derive(Debug, Clone)]
+struct UserFunction {
+    // name of the LLVM function
+    symbol: String,
+    arity: usize,
+}
+
+/// What a call expression calls.
+enum Callee {
+    User(UserFunction),
+    // index into `Compiler::host_functions`
+    Host(usize),
+    Builtin(&'static Builtin),
+}
+
+/// Call depth shared with the compiled code, so runaway recursion ends in a
+/// [`HitungError::RecursionLimit`] instead of a stack overflow.
+#[derive(Debug)]
+struct RecursionGuard {
+    depth: Cell<u64>,
+    limit: Cell<u64>,
+    // set when a call went past `limit`, from then on every call returns
+    // right away so the stack unwinds
+    exceeded: Cell<u64>,
+}
+
+impl RecursionGuard {
+    /// Get ready for the next run, returning whether the last one went past
+    /// the limit.
+    fn reset(&self) -> bool {
+        self.depth.set(0);
+        self.exceeded.replace(0) != 0
+    }
+}
+
+/// Session state the compiled code reaches through raw addresses, held by
+/// the handles so it outlives the compiler.
+struct Runtime {
+    recursion: Rc<RecursionGuard>,
+    // only held so their addresses stay valid for the compiled code
+    _slots: Vec<Rc<Cell<f64>>>,
+    _host_functions: Vec<Rc<HostFunction>>,
+}
+
+/// A formula compiled by [`Compiler::compile_fn`], callable as often as
+/// needed. Keeps the execution engine and the state the compiled code uses
+/// alive, even after the compiler is dropped.
+pub struct CompiledFn<'ctx> {
+    function: JitFunction<'ctx, FormulaSign>,
+    params: Vec<String>,
+    runtime: Runtime,
+}
+
+impl<'ctx> CompiledFn<'ctx> {
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    /// Evaluate the formula with `args` given in the order of the
+    /// parameters. Recursion deeper than the limit evaluates to NaN.
+    ///
+    /// # Panics
+    ///
+    /// When the number of `args` differs from the number of parameters.
+    pub fn call(&self, args: &[f64]) -> f64 {
+        assert_eq!(
+            self.params.len(),
+            args.len(),
+            "formula takes {} argument(s) but {} were given",
+            self.params.len(),
+            args.len()
+        );
+
+        let value = unsafe { self.function.call(args.as_ptr()) };
+        if self.runtime.recursion.reset() {
+            return f64::NAN;
+        }
+
+        value
+    }
+}
+
+impl fmt::Debug for CompiledFn<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompiledFn")
+            .field("params", &self.params)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A formula compiled by [`Compiler::compile_batch`] into a loop over
+/// columns of data, one column for every parameter.
+pub struct CompiledBatch<'ctx> {
+    function: JitFunction<'ctx, BatchSign>,
+    params: Vec<String>,
+    runtime: Runtime,
+}
+
+impl<'ctx> CompiledBatch<'ctx> {
+    pub fn params(&self) -> &[String] {
+        &self.params
+    }
+
+    /// Evaluate the formula for every row of `columns`, given in the order
+    /// of the parameters. Without parameters there are no rows, the result
+    /// is empty.
+    ///
+    /// Recursion deeper than the limit in any row makes every value NaN, the
+    /// rows after it cannot be trusted.
+    ///
+    /// # Panics
+    ///
+    /// When the number of `columns` differs from the number of parameters or
+    /// the columns differ in length.
+    pub fn call(&self, columns: &[&[f64]]) -> Vec<f64> {
+        assert_eq!(
+            self.params.len(),
+            columns.len(),
+            "formula takes {} column(s) but {} were given",
+            self.params.len(),
+            columns.len()
+        );
+        let rows = columns.first().map_or(0, |column| column.len());
+        assert!(
+            columns.iter().all(|column| column.len() == rows),
+            "every column must have the same length"
+        );
+
+        let pointers: Vec<*const f64> = columns.iter().map(|column| column.as_ptr()).collect();
+        let mut results = vec![0.0; rows];
+        unsafe {
+            self.function
+                .call(pointers.as_ptr(), results.as_mut_ptr(), rows as u64)
+        };
+        if self.runtime.recursion.reset() {
+            results.fill(f64::NAN);
+        }
+
+        results
+    }
+}
+
+impl fmt::Debug for CompiledBatch<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompiledBatch")
+            .field("params", &self.params)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The CPU the compiled code runs on, batch loops are optimised for it.
+struct Host {
+    machine: TargetMachine,
+    triple: TargetTriple,
+    cpu: String,
+    features: String,
+}
+
+impl Host {
+    fn new() -> std::result::Result<Self, String> {
+        Target::initialize_native(&InitializationConfig::default())?;
+        let triple = TargetMachine::get_default_triple();
+        let target = Target::from_triple(&triple).map_err(|err| err.to_string())?;
+        let cpu = TargetMachine::get_host_cpu_name().to_string();
+        let features = TargetMachine::get_host_cpu_features().to_string();
+        let machine = target
+            .create_target_machine(
+                &triple,
+                &cpu,
+                &features,
+                OptimizationLevel::Aggressive,
+                RelocMode::Default,
+                CodeModel::JITDefault,
+            )
+            .ok_or_else(|| "cannot create a target machine for the host".to_string())?;
+
+        Ok(Host {
+            machine,
+            triple,
+            cpu,
+            features,
+        })
+    }
+}
 
 pub struct Compiler<'ctx> {
     context: &'ctx Context,
+    // module the current input is compiled into
     module: Module<'ctx>,
     builder: Builder<'ctx>,
+    execution_engine: ExecutionEngine<'ctx>,
+    // modules already handed over to the execution engine
+    modules: Vec<Module<'ctx>>,
 
-    variables: HashMap<String, PointerValue<'ctx>>,
+    environment: Environment,
+    functions: HashMap<String, UserFunction>,
+    // every closure ever registered, replaced ones are kept alive because
+    // already compiled code may still call them
+    host_functions: Vec<Rc<HostFunction>>,
+    host_names: HashMap<String, usize>,
+    // parameters of the function being compiled
+    locals: HashMap<String, PointerValue<'ctx>>,
+    // behind a pointer so its address stays put for the compiled code
+    recursion: Rc<RecursionGuard>,
+    // created by the first `compile_batch`
+    host: Option<Host>,
+    dialect: Dialect,
     debug: bool,
 }
 
 impl<'ctx> Compiler<'ctx> {
     pub fn new(context: &'ctx Context, debug: bool) -> Self {
         let module = context.create_module("hitung");
+        let execution_engine = module
+            .create_jit_execution_engine(OptimizationLevel::None)
+            .expect("Error when create execution engine");
         let builder = context.create_builder();
 
         Compiler {
             context,
-            module,
+            module: context.create_module("hitung.1"),
             builder,
-            variables: HashMap::new(),
+            execution_engine,
+            modules: vec![module],
+            environment: Environment::new(),
+            functions: HashMap::new(),
+            host_functions: Vec::new(),
+            host_names: HashMap::new(),
+            locals: HashMap::new(),
+            recursion: Rc::new(RecursionGuard {
+                depth: Cell::new(0),
+                limit: Cell::new(DEFAULT_RECURSION_LIMIT),
+                exceeded: Cell::new(0),
+            }),
+            host: None,
+            dialect: Dialect::default(),
             debug,
         }
     }
-    pub fn compile_source(&mut self, source: &str) -> Result<f64, String> {
-        let lexer = Lexer::new(source);
-        let tokens = lexer.lex();
-        let mut parser = Parser::new(tokens);
-        match parser.expr(0) {
-            Ok(expression) => self.jit_compile(expression),
-            Err(e) => Err(e),
+
+    /// Maximum depth of nested function calls, also applies to functions
+    /// compiled before the limit was changed.
+    pub fn set_recursion_limit(&mut self, limit: u64) {
+        self.recursion.limit.set(limit);
+    }
+
+    /// Make the Rust closure `function` callable as `name` from the following
+    /// inputs, e.g. `compiler.register_fn("pajak", |x: f64| x * 0.11)`.
+    ///
+    /// Functions defined with `fungsi` hide a registered one with the same
+    /// name, registered ones hide the builtins.
+    pub fn register_fn<Args, F: HostFn<Args>>(&mut self, name: &str, function: F) {
+        let index = self.host_functions.len();
+        let symbol = format!("host.{}.{}", name, index);
+
+        self.host_functions
+            .push(Rc::new(HostFunction::new(symbol, function)));
+        self.host_names.insert(name.to_string(), index);
+    }
+
+    /// Which keywords the following inputs are written with.
+    pub fn set_dialect(&mut self, dialect: Dialect) {
+        self.dialect = dialect;
+    }
+
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    /// Current value of the session variable `name`.
+    pub fn variable(&self, name: &str) -> Option<f64> {
+        self.environment.get(name)
+    }
+
+    /// Evaluate every statement in `source`, returning the value of the last
+    /// one or `None` when there is nothing to evaluate.
+    pub fn compile_source(&mut self, source: &str) -> Result<Option<f64>> {
+        let program = self.parse(source)?;
+        self.jit_compile(program)
+    }
+
+    /// Evaluate every statement in `source`, returning the value of each one
+    /// in order.
+    pub fn compile_statements(&mut self, source: &str) -> Result<Vec<f64>> {
+        let program = self.parse(source)?;
+        self.run_program(program)
+    }
+
+    /// Compile `source` once into a native function of `params`, e.g.
+    /// `compile_fn("harga * (1 + pajak) - diskon", &["harga", "pajak", "diskon"])`.
+    ///
+    /// Parameters hide session variables and constants with the same name.
+    /// The formula may still read and assign the other session variables and
+    /// call the functions defined so far.
+    pub fn compile_fn(&mut self, source: &str, params: &[&str]) -> Result<CompiledFn<'ctx>> {
+        let name = format!("rumus.{}", self.modules.len());
+        let span = self.add_formula(source, params, &name, Compiler::compile_formula)?;
+
+        let function = unsafe { self.execution_engine.get_function::<FormulaSign>(&name) }
+            .map_err(|err| HitungError::Llvm {
+                message: err.to_string(),
+                span,
+            })?;
+
+        Ok(CompiledFn {
+            function,
+            params: params.iter().map(|param| param.to_string()).collect(),
+            runtime: self.runtime(),
+        })
+    }
+
+    /// Compile `source` into a loop evaluating it for every row of a set of
+    /// columns, one column for every name in `params`. The loop is optimised
+    /// for the host CPU, so formulas of plain arithmetic run as vector code.
+    ///
+    /// Parameters are scoped like the ones of [`Compiler::compile_fn`].
+    pub fn compile_batch(&mut self, source: &str, params: &[&str]) -> Result<CompiledBatch<'ctx>> {
+        let name = format!("rumus.{}", self.modules.len());
+        let span = self.add_formula(source, params, &name, Compiler::compile_batch_loop)?;
+
+        let function =
+            unsafe { self.execution_engine.get_function::<BatchSign>(&name) }.map_err(|err| {
+                HitungError::Llvm {
+                    message: err.to_string(),
+                    span,
+                }
+            })?;
+
+        Ok(CompiledBatch {
+            function,
+            params: params.iter().map(|param| param.to_string()).collect(),
+            runtime: self.runtime(),
+        })
+    }
+
+    /// Check the formula `source`, emit it as the function `name` with
+    /// `emit` and hand it over to the execution engine. Returns the span of
+    /// the formula.
+    fn add_formula(
+        &mut self,
+        source: &str,
+        params: &[&str],
+        name: &str,
+        emit: fn(&mut Self, Program, &str, &[&str]) -> Result<()>,
+    ) -> Result<Span> {
+        let program = self.parse(source)?;
+        let span = program.span().unwrap_or_default();
+        if program.statements.is_empty() {
+            return Err(HitungError::UnexpectedEof { span });
+        }
+        for (index, param) in params.iter().enumerate() {
+            if params[..index].contains(param) {
+                return Err(HitungError::DuplicateParameter {
+                    name: param.to_string(),
+                    span,
+                });
+            }
+        }
+
+        let variables = self.environment.names();
+        let functions = self.functions.clone();
+        if let Err(err) = emit(self, program, name, params) {
+            self.discard(&variables, functions);
+            return Err(err);
+        }
+        self.add_module(span, &variables, functions)?;
+
+        Ok(span)
+    }
+
+    fn runtime(&self) -> Runtime {
+        Runtime {
+            recursion: Rc::clone(&self.recursion),
+            _slots: self.environment.slots(),
+            _host_functions: self.host_functions.clone(),
         }
     }
 
-    pub fn jit_compile(&mut self, expr: Expression) -> Result<f64, String> {
-        let float = self.context.f64_type();
-        let fn_type = float.fn_type(&[], false);
-        let function = self.module.add_function("berhitung", fn_type, None);
-        let basic_block = self.context.append_basic_block(function, "entry");
-        self.builder.position_at_end(basic_block);
+    fn parse(&self, source: &str) -> Result<Program> {
+        let lexer = Lexer::with_dialect(source, self.dialect);
+        let tokens = lexer.lex()?;
+        let mut parser = Parser::new(tokens).with_source(source);
+        let program = parser.program()?;
+
+        if self.debug {
+            println!("AST:\n{:#?}", program);
+        }
 
-        let return_val = self.eval(expr)?;
-        self.builder.build_return(Some(&return_val));
+        Ok(program)
+    }
 
-        let execution_engine = self
-            .module
-            .create_jit_execution_engine(OptimizationLevel::None)
-            .map_err(|e| e.to_string())?;
+    pub fn jit_compile(&mut self, program: Program) -> Result<Option<f64>> {
+        let values = self.run_program(program)?;
+        Ok(values.last().copied())
+    }
+
+    /// Run `program`, returning the value of each of its statements in order.
+    pub fn run_program(&mut self, program: Program) -> Result<Vec<f64>> {
+        let span = match program.span() {
+            Some(span) => span,
+            None => return Ok(Vec::new()),
+        };
+        let name = format!("berhitung.{}", self.modules.len());
+
+        // filled in by the compiled code with the value of each statement
+        let results = vec![Cell::new(0.0); program.statements.len()];
+
+        let variables = self.environment.names();
+        let functions = self.functions.clone();
+        let has_statements = match self.compile_program(program, &name, &results) {
+            Ok(has_statements) => has_statements,
+            Err(err) => {
+                self.discard(&variables, functions);
+                return Err(err);
+            }
+        };
+
+        self.add_module(span, &variables, functions)?;
+
+        // only function definitions, nothing to run
+        if !has_statements {
+            return Ok(Vec::new());
+        }
+
+        let function_calc = unsafe { self.execution_engine.get_function::<FuncSign>(&name) };
 
-        let last_func = self.module.get_last_function().expect("Error when get last function");
-        let last_func_name = last_func.get_name().to_str().expect("Error when get last function name");
+        match function_calc {
+            Ok(f) => {
+                unsafe { f.call() };
+                if self.recursion.reset() {
+                    return Err(HitungError::RecursionLimit {
+                        limit: self.recursion.limit.get(),
+                        span,
+                    });
+                }
 
-        let function_calc = unsafe { execution_engine.get_function::<FuncSign>(last_func_name) };
+                Ok(results.iter().map(Cell::get).collect())
+            }
+            Err(err) => Err(HitungError::Llvm {
+                message: err.to_string(),
+                span,
+            }),
+        }
+    }
 
+    /// Hand the finished module over to the execution engine. On failure
+    /// the session goes back to `variables` and `functions`.
+    fn add_module(
+        &mut self,
+        span: Span,
+        variables: &[String],
+        functions: HashMap<String, UserFunction>,
+    ) -> Result<()> {
         if self.debug {
             println!("LLVM IR:");
-            function.print_to_stderr();
+            self.module.print_to_stderr();
             self.module
                 .print_to_file(Path::new("hitung.ll"))
                 .expect("Error print to file");
         }
 
-        match execution_engine.remove_module(&self.module) {
-            Ok(_ok) => match function_calc {
-                Ok(f) => Ok(unsafe { f.call() }),
-                Err(err) => {
-                    println!("!> Error during execution: {:?}", err);
-                    Err(err.to_string())
-                }
-            },
-            Err(err) => Err(err.to_string()),
+        let module = self.next_module();
+        if self.execution_engine.add_module(&module).is_err() {
+            self.environment.retain(variables);
+            self.functions = functions;
+            return Err(HitungError::Llvm {
+                message: "module is already in the execution engine".to_string(),
+                span,
+            });
         }
+        self.modules.push(module);
+
+        Ok(())
+    }
+
+    /// Swap in an empty module for the next input and return the finished one.
+    fn next_module(&mut self) -> Module<'ctx> {
+        let name = format!("hitung.{}", self.modules.len() + 1);
+        mem::replace(&mut self.module, self.context.create_module(&name))
     }
 
-    fn eval(
+    /// Throw away the half built module so the next input starts from the
+    /// state the session had before it. `variables` and `functions` are the
+    /// ones that existed before the failed input.
+    fn discard(&mut self, variables: &[String], functions: HashMap<String, UserFunction>) {
+        self.builder.clear_insertion_position();
+        drop(self.next_module());
+        self.environment.retain(variables);
+        self.functions = functions;
+        self.locals.clear();
+    }
+
+    /// Emit the functions of `program` and, when there are any statements,
+    /// a function `name` running them. Returns whether `name` was emitted.
+    fn compile_program(
         &mut self,
-        expression: Expression,
-    ) -> Result<inkwell::values::FloatValue<'ctx>, String> {
-        match expression {
-            Expression::Variable(name) => match self.variables.get(&name) {
-                Some(value) => {
-                    let val = self.builder.build_load(*value, name.as_str());
-                    Ok(val.into_float_value())
-                }
-                None => Err("Variable not declared".to_string()),
-            },
-            Expression::Num(n) => {
-                let float = self.context.f64_type();
-                Ok(float.const_float(n as f64))
+        program: Program,
+        name: &str,
+        results: &[Cell<f64>],
+    ) -> Result<bool> {
+        self.declare_functions(&program.functions)?;
+
+        let has_statements = !program.statements.is_empty();
+        if has_statements {
+            let float = self.context.f64_type();
+            let fn_type = float.fn_type(&[], false);
+            let function = self.module.add_function(name, fn_type, None);
+            let basic_block = self.context.append_basic_block(function, "entry");
+            self.builder.position_at_end(basic_block);
+
+            let return_val = self.eval_statements(program.statements, results)?;
+            self.builder.build_return(Some(&return_val));
+        }
+
+        self.compile_functions(program.functions)?;
+
+        Ok(has_statements)
+    }
+
+    /// Check the definitions of one input and add their prototypes, so the
+    /// statements and the bodies can call every one of them.
+    ///
+    /// Bodies are compiled after the statements, by then every variable the
+    /// statements assign has its slot and the bodies can read it.
+    fn declare_functions(&mut self, functions: &[Spanned<Function>]) -> Result<()> {
+        // every definition of one input shares a symbol, so each name may
+        // only be defined once per input
+        for (index, function) in functions.iter().enumerate() {
+            let name = &function.node.name;
+            if functions[..index]
+                .iter()
+                .any(|other| other.node.name.node == name.node)
+            {
+                return Err(HitungError::DuplicateFunction {
+                    name: name.node.clone(),
+                    span: name.span,
+                });
             }
-            Expression::Unary(operator, expr) => match operator {
-                Token::Add => {
-                    let num = self.eval(*expr)?;
+        }
 
-                    Ok(num)
-                },
-                Token::Sub => {
-                    let float = self.context.f64_type();
-                    let num = self.eval(*expr)?;
-                    let rhs = float.const_float_from_string("-1");
-                    let sum = self.builder.build_float_mul(num, rhs, "mul");
-                    Ok(sum)
-                }
-                _ => Err("Expression for Unary must be + or -".to_string()),
-            },
-            Expression::Binary(left, operator, right) => match operator {
-                Token::LT | Token::GT => {
-                    let lhs = self.eval(*left)?;
-                    let rhs = self.eval(*right)?;
+        for function in functions {
+            self.declare_function(function)?;
+        }
 
-                    let predicate = match operator {
-                        Token::LT => FloatPredicate::OLT,
-                        Token::GT => FloatPredicate::OGT,
-                        _ => return Err("Operator not supported".to_string()),
-                    };
+        Ok(())
+    }
 
-                    let conditional = self.builder.build_float_compare(predicate, lhs, rhs, "if");
+    fn compile_functions(&mut self, functions: Vec<Spanned<Function>>) -> Result<()> {
+        for function in functions {
+            self.compile_function(function.node)?;
+        }
 
-                    Ok(self.builder.build_unsigned_int_to_float(
-                        conditional,
-                        self.context.f64_type(),
-                        "bool",
-                    ))
-                },
-                Token::ASSIGN => match *left {
-                    Expression::Variable(var) => {
-                        let f64_type = self.context.f64_type();
-                        let global = self.module.add_global(f64_type, None, var.as_str());
-                        global.set_initializer(&f64_type.const_float(0 as f64));
-                        let rhs = self.eval(*right)?;
-                        global.set_initializer(&rhs);
-                        self.builder.build_store(global.as_pointer_value(), rhs);
+        Ok(())
+    }
 
-                        let value = self
-                            .builder
-                            .build_load(global.as_pointer_value(), var.as_str());
-                        self.variables.insert(var, global.as_pointer_value());
+    /// Emit the functions of `program` and a function `name` evaluating its
+    /// statements with `params` read from the array passed as its argument.
+    fn compile_formula(&mut self, program: Program, name: &str, params: &[&str]) -> Result<()> {
+        self.declare_functions(&program.functions)?;
 
-                        Ok(value.into_float_value())
-                    }
-                    _ => Err("Assignment must be a variable".to_string()),
-                },
-                _ => {
-                    let lhs = self.eval(*left)?;
-                    let rhs = self.eval(*right)?;
+        let float = self.context.f64_type();
+        let fn_type = float.fn_type(&[float.ptr_type(AddressSpace::Generic).into()], false);
+        let function = self.module.add_function(name, fn_type, None);
+        let basic_block = self.context.append_basic_block(function, "entry");
+        self.builder.position_at_end(basic_block);
 
-                    match operator {
-                        Token::Add => Ok(self.builder.build_float_add(lhs, rhs, "add")),
-                        Token::Sub => Ok(self.builder.build_float_sub(lhs, rhs, "sub")),
-                        Token::Mul => Ok(self.builder.build_float_mul(lhs, rhs, "mul")),
-                        Token::Div => Ok(self.builder.build_float_div(lhs, rhs, "div")),
-                        _ => Err("Operator not supported".to_string()),
-                    }
-                }
-            },
-            Expression::Paren(expr) => {
-                let result = &self.eval(*expr)?;
-                Ok(*result)
-            }
-            Expression::Conditional(cond, then, els) => {
-                let if_cond = self.eval(*cond)?;
+        let args = function
+            .get_nth_param(0)
+            .expect("formula takes the argument array")
+            .into_pointer_value();
+        self.locals.clear();
+        for (index, param) in params.iter().enumerate() {
+            let index = self.context.i64_type().const_int(index as u64, false);
+            let pointer = unsafe { self.builder.build_in_bounds_gep(args, &[index], param) };
+            let value = self.builder.build_load(pointer, param);
 
-                let function = self.module.get_last_function().expect("Error when get last function");
+            // copied so assigning to a parameter never writes to the caller's slice
+            let local = self.builder.build_alloca(float, param);
+            self.builder.build_store(local, value);
+            self.locals.insert(param.to_string(), local);
+        }
 
-                let then_block = self.context.append_basic_block(function, "entry");
-                let else_block = self.context.append_basic_block(function, "entry");
-                let cont_block = self.context.append_basic_block(function, "entry");
+        let value = self.eval_statements(program.statements, &[]);
+        self.locals.clear();
+        self.builder.build_return(Some(&value?));
 
-                let i64_type = self.context.i64_type();
+        self.compile_functions(program.functions)
+    }
 
-                self.builder.build_conditional_branch(
-                    if_cond.const_to_unsigned_int(i64_type),
-                    then_block,
-                    else_block,
-                );
-                self.builder.position_at_end(then_block);
+    /// Emit the functions of `program` and a function `name` looping over
+    /// the rows of the columns passed as its first argument, one for each of
+    /// `params`, and storing the value of every row into its second one.
+    fn compile_batch_loop(&mut self, program: Program, name: &str, params: &[&str]) -> Result<()> {
+        let span = program.span().unwrap_or_default();
+        self.declare_functions(&program.functions)?;
 
-                let then_value = self.eval(*then)?;
-                self.builder.build_unconditional_branch(cont_block);
-                let then_block = self.builder.get_insert_block().expect("Error when get insert block");
+        let float = self.context.f64_type();
+        let index_type = self.context.i64_type();
+        let column_type = float.ptr_type(AddressSpace::Generic);
+        let fn_type = self.context.void_type().fn_type(
+            &[
+                column_type.ptr_type(AddressSpace::Generic).into(),
+                column_type.into(),
+                index_type.into(),
+            ],
+            false,
+        );
+        let function = self.module.add_function(name, fn_type, None);
 
-                // build else block
-                self.builder.position_at_end(else_block);
-                let else_value = self.eval(*els)?;
-                self.builder.build_unconditional_branch(cont_block);
+        // the results never overlap the columns, without this LLVM has to
+        // assume every store may change the next row and cannot vectorise
+        let noalias = Attribute::get_named_enum_kind_id("noalias");
+        for index in 0..2 {
+            function.add_attribute(
+                AttributeLoc::Param(index),
+                self.context.create_enum_attribute(noalias, 0),
+            );
+        }
 
-                let else_block = self.builder.get_insert_block().expect("Error when get insert block");
-                self.builder.position_at_end(cont_block);
+        let entry_block = self.context.append_basic_block(function, "entry");
+        let cond_block = self.context.append_basic_block(function, "batch.cond");
+        let body_block = self.context.append_basic_block(function, "batch.body");
+        let end_block = self.context.append_basic_block(function, "batch.end");
+        self.builder.position_at_end(entry_block);
 
-                let phi = self.builder.build_phi(self.context.f64_type(), "entry");
-                phi.add_incoming(&[(&then_value, then_block), (&else_value, else_block)]);
+        let mut params_iter = function.get_param_iter();
+        let columns_arg = params_iter
+            .next()
+            .expect("batch takes columns")
+            .into_pointer_value();
+        let results = params_iter
+            .next()
+            .expect("batch takes results")
+            .into_pointer_value();
+        let rows = params_iter
+            .next()
+            .expect("batch takes rows")
+            .into_int_value();
 
-                Ok(phi.as_basic_value().into_float_value())
-            }
+        let mut columns = Vec::new();
+        for (index, param) in params.iter().enumerate() {
+            let index = index_type.const_int(index as u64, false);
+            let pointer = unsafe {
+                self.builder
+                    .build_in_bounds_gep(columns_arg, &[index], param)
+            };
+            columns.push(self.builder.build_load(pointer, param).into_pointer_value());
         }
+        self.builder.build_unconditional_branch(cond_block);
+
+        self.builder.position_at_end(cond_block);
+        let phi = self.builder.build_phi(index_type, "row");
+        phi.add_incoming(&[(&index_type.const_int(0, false), entry_block)]);
+        let row = phi.as_basic_value().into_int_value();
+        let more = self
+            .builder
+            .build_int_compare(IntPredicate::ULT, row, rows, "more");
+        self.builder
+            .build_conditional_branch(more, body_block, end_block);
+
+        self.builder.position_at_end(body_block);
+        self.locals.clear();
+        for (param, column) in params.iter().zip(columns) {
+            let pointer = unsafe { self.builder.build_in_bounds_gep(column, &[row], param) };
+            let value = self.builder.build_load(pointer, param);
+
+            let local = self.entry_alloca(param);
+            self.builder.build_store(local, value);
+            self.locals.insert(param.to_string(), local);
+        }
+
+        let value = self.eval_statements(program.statements, &[]);
+        self.locals.clear();
+        let pointer = unsafe { self.builder.build_in_bounds_gep(results, &[row], "result") };
+        self.builder.build_store(pointer, value?);
+
+        let next = self
+            .builder
+            .build_int_add(row, index_type.const_int(1, false), "next");
+        self.builder.build_unconditional_branch(cond_block);
+        let body_block = self
+            .builder
+            .get_insert_block()
+            .expect("Error when get insert block");
+        phi.add_incoming(&[(&next, body_block)]);
+
+        self.builder.position_at_end(end_block);
+        self.builder.build_return(None);
+        self.compile_functions(program.functions)?;
+
+        self.vectorise(function)
+            .map_err(|message| HitungError::Llvm { message, span })
     }
-}
 
-#[cfg(test)]
-mod test {
-    use super::*;
+    /// Optimise `function` for the host CPU, turning its loop into vector
+    /// code where LLVM can.
+    fn vectorise(&mut self, function: FunctionValue<'ctx>) -> std::result::Result<(), String> {
+        if self.host.is_none() {
+            self.host = Some(Host::new()?);
+        }
+        let host = self.host.as_ref().expect("host is set above");
 
-    #[test]
-    fn test_eval_from_expression() {
-        let expression = Expression::Binary(
-            Box::new(Expression::Binary(
-                Box::new(Expression::from(3)),
-                Token::Add,
-                Box::new(Expression::from(2)),
-            )),
-            Token::Sub,
-            Box::new(Expression::from(2)),
-        );
+        // the engine compiles for a generic CPU unless the function asks for
+        // the one the loop was vectorised for
+        for (key, value) in [
+            ("target-cpu", &host.cpu),
+            ("target-features", &host.features),
+        ] {
+            function.add_attribute(
+                AttributeLoc::Function,
+                self.context.create_string_attribute(key, value),
+            );
+        }
+        self.module.set_triple(&host.triple);
+        self.module
+            .set_data_layout(&host.machine.get_target_data().get_data_layout());
 
-        let context = Context::create();
-        let module = context.create_module("test_hitung");
-        let builder = context.create_builder();
+        let passes = PassManager::create(&self.module);
+        // cost model of the host, without it nothing is worth vectorising
+        host.machine.add_analysis_passes(&passes);
+        passes.add_promote_memory_to_register_pass();
+        passes.add_instruction_combining_pass();
+        passes.add_reassociate_pass();
+        passes.add_gvn_pass();
+        passes.add_cfg_simplification_pass();
+        passes.add_loop_rotate_pass();
+        passes.add_licm_pass();
+        passes.add_ind_var_simplify_pass();
+        passes.add_loop_vectorize_pass();
+        passes.add_slp_vectorize_pass();
+        passes.add_instruction_combining_pass();
+        passes.add_cfg_simplification_pass();
 
-        let mut compiler = Compiler {
-            context: &context,
-            module,
-            builder,
-            variables: Default::default(),
-            debug: false,
+        passes.initialize();
+        passes.run_on(&function);
+
+        Ok(())
+    }
+
+    /// Evaluate the statements in order, storing each value into `results`
+    /// when it has room for it, and return the value of the last one.
+    fn eval_statements(
+        &mut self,
+        statements: Vec<Spanned<Expression>>,
+        results: &[Cell<f64>],
+    ) -> Result<FloatValue<'ctx>> {
+        let mut last = self.context.f64_type().const_float(0.0);
+
+        for (index, statement) in statements.into_iter().enumerate() {
+            last = self.eval(statement)?;
+
+            if let Some(result) = results.get(index) {
+                let pointer = self.slot_pointer(result.as_ptr() as usize);
+                self.builder.build_store(pointer, last);
+            }
+        }
+
+        Ok(last)
+    }
+
+    /// Register `function` and add its prototype to the current module.
+    ///
+    /// Every definition gets its own symbol, so redefining a function in a
+    /// later input never clashes with the module holding the old one.
+    fn declare_function(&mut self, function: &Spanned<Function>) -> Result<()> {
+        let params = &function.node.params;
+        for (index, param) in params.iter().enumerate() {
+            if params[..index].iter().any(|other| other.node == param.node) {
+                return Err(HitungError::DuplicateParameter {
+                    name: param.node.clone(),
+                    span: param.span,
+                });
+            }
+        }
+
+        let name = &function.node.name.node;
+        let user_function = UserFunction {
+            symbol: format!("fungsi.{}.{}", name, self.modules.len()),
+            arity: params.len(),
         };
+        self.prototype(&user_function);
+        self.functions.insert(name.clone(), user_function);
 
-        let actual = compiler.jit_compile(expression).unwrap();
-        assert_eq!(3.0, actual);
+        Ok(())
     }
 
-    #[test]
-    fn test_eval_from_source() {
-        let context = Context::create();
-        let mut compiler = Compiler::new(&context, false);
+    fn compile_function(&mut self, function: Function) -> Result<()> {
+        let user_function = self.functions[&function.name.node].clone();
+        let function_value = self.prototype(&user_function);
 
-        let actual = compiler.compile_source(r"2 + 2 * 3 / 2").unwrap();
+        let entry = self.context.append_basic_block(function_value, "entry");
+        self.builder.position_at_end(entry);
+
+        // parameters live in stack slots so the body can assign to them
+        self.locals.clear();
+        for (index, param) in function.params.into_iter().enumerate() {
+            let value = function_value
+                .get_nth_param(index as u32)
+                .expect("Error when get parameter");
+            let pointer = self
+                .builder
+                .build_alloca(self.context.f64_type(), &param.node);
+            self.builder.build_store(pointer, value);
+            self.locals.insert(param.node, pointer);
+        }
+
+        self.enter_call(function_value);
+        let body = self.eval(function.body);
+        self.locals.clear();
+        let body = body?;
+        self.add_to_depth(-1);
+        self.builder.build_return(Some(&body));
 
-        assert_eq!(5.0, actual);
+        Ok(())
     }
 
-    #[test]
-    fn test_eval_from_source_if_then_else() {
+    /// Count the call in the recursion depth, returning `0` right away when
+    /// it is one call too many.
+    fn enter_call(&self, function: FunctionValue<'ctx>) {
+        let int = self.context.i64_type();
+        let zero = int.const_int(0, false);
+
+        let depth = self.load_counter(&self.recursion.depth, "depth");
+        let limit = self.load_counter(&self.recursion.limit, "limit");
+        let exceeded = self.load_counter(&self.recursion.exceeded, "exceeded");
+        let too_deep = self
+            .builder
+            .build_int_compare(IntPredicate::UGE, depth, limit, "toodeep");
+        let stopped = self
+            .builder
+            .build_int_compare(IntPredicate::NE, exceeded, zero, "stopped");
+        let stop = self.builder.build_or(too_deep, stopped, "stop");
+
+        let stop_block = self.context.append_basic_block(function, "recursion.stop");
+        let body_block = self.context.append_basic_block(function, "body");
+        self.builder
+            .build_conditional_branch(stop, stop_block, body_block);
+
+        self.builder.position_at_end(stop_block);
+        let exceeded_pointer = self.counter_pointer(&self.recursion.exceeded);
+        self.builder
+            .build_store(exceeded_pointer, int.const_int(1, false));
+        let zero = self.context.f64_type().const_float(0.0);
+        self.builder.build_return(Some(&zero));
+
+        self.builder.position_at_end(body_block);
+        self.add_to_depth(1);
+    }
+
+    fn add_to_depth(&self, amount: i64) {
+        let depth = self.load_counter(&self.recursion.depth, "depth");
+        let amount = self.context.i64_type().const_int(amount as u64, true);
+        let depth = self.builder.build_int_add(depth, amount, "depth");
+        self.builder
+            .build_store(self.counter_pointer(&self.recursion.depth), depth);
+    }
+
+    /// Jump to `exit` once a call went past the recursion limit. Calls
+    /// return `0` from then on, so a loop waiting for a call result would
+    /// otherwise never end.
+    fn leave_if_exceeded(&self, exit: BasicBlock<'ctx>) {
+        let zero = self.context.i64_type().const_int(0, false);
+        let exceeded = self.load_counter(&self.recursion.exceeded, "exceeded");
+        let stopped = self
+            .builder
+            .build_int_compare(IntPredicate::NE, exceeded, zero, "stopped");
+
+        let next_block = self
+            .context
+            .append_basic_block(self.current_function(), "recursion.ok");
+        self.builder
+            .build_conditional_branch(stopped, exit, next_block);
+        self.builder.position_at_end(next_block);
+    }
+
+    fn load_counter(&self, counter: &Cell<u64>, name: &str) -> IntValue<'ctx> {
+        self.builder
+            .build_load(self.counter_pointer(counter), name)
+            .into_int_value()
+    }
+
+    /// Pointer to a counter owned by Rust, see [`RecursionGuard`].
+    fn counter_pointer(&self, counter: &Cell<u64>) -> PointerValue<'ctx> {
+        let i64_ptr = self.context.i64_type().ptr_type(AddressSpace::Generic);
+
+        self.context
+            .i64_type()
+            .const_int(counter.as_ptr() as u64, false)
+            .const_to_pointer(i64_ptr)
+    }
+
+    /// `function` in the current module, declared when it is not there yet.
+    fn prototype(&self, function: &UserFunction) -> FunctionValue<'ctx> {
+        self.module
+            .get_function(&function.symbol)
+            .unwrap_or_else(|| {
+                let float = self.context.f64_type();
+                let params = vec![float.into(); function.arity];
+                let fn_type = float.fn_type(&params, false);
+                self.module.add_function(&function.symbol, fn_type, None)
+            })
+    }
+
+    /// Stack slot in the entry block of the current function, so a slot
+    /// created inside a loop is not allocated again on every iteration.
+    fn entry_alloca(&self, name: &str) -> PointerValue<'ctx> {
+        let builder = self.context.create_builder();
+        let entry = self
+            .current_function()
+            .get_first_basic_block()
+            .expect("Error when get entry block");
+
+        match entry.get_first_instruction() {
+            Some(instruction) => builder.position_before(&instruction),
+            None => builder.position_at_end(entry),
+        }
+
+        builder.build_alloca(self.context.f64_type(), name)
+    }
+
+    /// Function the builder is currently emitting code into.
+    fn current_function(&self) -> FunctionValue<'ctx> {
+        self.builder
+            .get_insert_block()
+            .and_then(|block| block.get_parent())
+            .expect("Error when get current function")
+    }
+
+    /// Any value other than `0` is true.
+    fn truth(&self, value: FloatValue<'ctx>) -> IntValue<'ctx> {
+        let zero = self.context.f64_type().const_float(0.0);
+        self.builder
+            .build_float_compare(FloatPredicate::ONE, value, zero, "truth")
+    }
+
+    /// `1` for true and `0` for false.
+    fn to_float(&self, value: IntValue<'ctx>) -> FloatValue<'ctx> {
+        self.builder
+            .build_unsigned_int_to_float(value, self.context.f64_type(), "bool")
+    }
+
+    /// Pointer to a `f64` owned by Rust, e.g. a variable slot of the
+    /// [`Environment`].
+    fn slot_pointer(&self, address: usize) -> PointerValue<'ctx> {
+        let f64_ptr = self.context.f64_type().ptr_type(AddressSpace::Generic);
+
+        self.context
+            .i64_type()
+            .const_int(address as u64, false)
+            .const_to_pointer(f64_ptr)
+    }
+
+    fn callee(&self, name: &str) -> Option<Callee> {
+        if let Some(function) = self.functions.get(name) {
+            return Some(Callee::User(function.clone()));
+        }
+        if let Some(index) = self.host_names.get(name) {
+            return Some(Callee::Host(*index));
+        }
+
+        Builtin::lookup(name).map(Callee::Builtin)
+    }
+
+    /// Call a registered closure through its trampoline. The declaration is
+    /// linked to the trampoline by the global mapping of the engine.
+    fn call_host(&self, index: usize, args: &[FloatValue<'ctx>]) -> FloatValue<'ctx> {
+        let host = &self.host_functions[index];
+        let address = self.context.i64_type();
+
+        let function = self.module.get_function(&host.symbol).unwrap_or_else(|| {
+            let float = self.context.f64_type();
+            let mut params = vec![address.into()];
+            params.resize(host.arity + 1, float.into());
+
+            let function =
+                self.module
+                    .add_function(&host.symbol, float.fn_type(&params, false), None);
+            self.execution_engine
+                .add_global_mapping(&function, host.trampoline);
+            function
+        });
+
+        let closure = address.const_int(host.closure() as u64, false);
+        let mut values: Vec<BasicValueEnum> = vec![closure.into()];
+        values.extend(args.iter().map(|arg| BasicValueEnum::from(*arg)));
+        self.builder
+            .build_call(function, &values, "host")
+            .try_as_basic_value()
+            .left()
+            .expect("Error when get return value")
+            .into_float_value()
+    }
+
+    /// Call the LLVM intrinsic or C math function `name` taking and returning
+    /// `f64`s, declaring it in the current module first when needed.
+    fn call_symbol(&self, name: &str, args: &[FloatValue<'ctx>]) -> FloatValue<'ctx> {
+        let function = self.module.get_function(name).unwrap_or_else(|| {
+            let float = self.context.f64_type();
+            let params = vec![float.into(); args.len()];
+            self.module
+                .add_function(name, float.fn_type(&params, false), None)
+        });
+
+        let args: Vec<_> = args.iter().map(|arg| (*arg).into()).collect();
+        self.builder
+            .build_call(function, &args, "intrinsic")
+            .try_as_basic_value()
+            .left()
+            .expect("Error when get return value")
+            .into_float_value()
+    }
+
+    /// Where `name` is stored: a parameter of the function being compiled
+    /// shadows a session variable with the same name.
+    fn variable_pointer(&self, name: &str) -> Option<PointerValue<'ctx>> {
+        match self.locals.get(name) {
+            Some(pointer) => Some(*pointer),
+            None => self
+                .environment
+                .address(name)
+                .map(|address| self.slot_pointer(address)),
+        }
+    }
+
+    fn eval(&mut self, expression: Spanned<Expression>) -> Result<FloatValue<'ctx>> {
+        let span = expression.span;
+
+        match expression.node {
+            Expression::Variable(name) => {
+                // parameters and loop counters hide the constants, session
+                // variables cannot
+                if !self.locals.contains_key(&name) {
+                    if let Some(constant) = Constant::lookup(&name) {
+                        return Ok(self.context.f64_type().const_float(constant.value));
+                    }
+                }
+
+                match self.variable_pointer(&name) {
+                    Some(pointer) => {
+                        let val = self.builder.build_load(pointer, name.as_str());
+                        Ok(val.into_float_value())
+                    }
+                    None => Err(HitungError::UndeclaredVariable { name, span }),
+                }
+            }
+            Expression::Call(name, args) => {
+                let callee = match self.callee(&name) {
+                    Some(callee) => callee,
+                    None => return Err(HitungError::UndeclaredFunction { name, span }),
+                };
+                let arity = match &callee {
+                    Callee::User(function) => function.arity,
+                    Callee::Host(index) => self.host_functions[*index].arity,
+                    Callee::Builtin(builtin) => builtin.arity,
+                };
+                if arity != args.len() {
+                    return Err(HitungError::ArityMismatch {
+                        name,
+                        expected: arity,
+                        found: args.len(),
+                        span,
+                    });
+                }
+
+                let mut values = Vec::with_capacity(args.len());
+                for arg in args {
+                    values.push(self.eval(arg)?);
+                }
+
+                match callee {
+                    Callee::User(function) => {
+                        let function = self.prototype(&function);
+                        let values: Vec<_> = values.into_iter().map(|value| value.into()).collect();
+                        let call = self.builder.build_call(function, &values, "call");
+
+                        Ok(call
+                            .try_as_basic_value()
+                            .left()
+                            .expect("Error when get return value")
+                            .into_float_value())
+                    }
+                    Callee::Host(index) => Ok(self.call_host(index, &values)),
+                    Callee::Builtin(builtin) => Ok(self.call_symbol(builtin.symbol, &values)),
+                }
+            }
+            Expression::Num(n) => {
+                let float = self.context.f64_type();
+                Ok(float.const_float(n as f64))
+            }
+            Expression::Unary(operator, expr) => match operator {
+                Token::Add => {
+                    let num = self.eval(*expr)?;
+
+                    Ok(num)
+                }
+                Token::Sub => {
+                    let float = self.context.f64_type();
+                    let num = self.eval(*expr)?;
+                    let rhs = float.const_float_from_string("-1");
+                    let sum = self.builder.build_float_mul(num, rhs, "mul");
+                    Ok(sum)
+                }
+                Token::Not => {
+                    let num = self.eval(*expr)?;
+                    let truth = self.truth(num);
+                    let not = self.builder.build_not(truth, "not");
+                    Ok(self.to_float(not))
+                }
+                operator => Err(HitungError::UnsupportedOperator { operator, span }),
+            },
+            Expression::Binary(left, operator, right) => match operator {
+                Token::EQ | Token::NEQ | Token::LT | Token::LTE | Token::GT | Token::GTE => {
+                    let lhs = self.eval(*left)?;
+                    let rhs = self.eval(*right)?;
+
+                    let predicate = match operator {
+                        Token::EQ => FloatPredicate::OEQ,
+                        // unordered, so `nan != nan` holds
+                        Token::NEQ => FloatPredicate::UNE,
+                        Token::LT => FloatPredicate::OLT,
+                        Token::LTE => FloatPredicate::OLE,
+                        Token::GT => FloatPredicate::OGT,
+                        Token::GTE => FloatPredicate::OGE,
+                        operator => {
+                            return Err(HitungError::UnsupportedOperator { operator, span })
+                        }
+                    };
+
+                    let conditional = self.builder.build_float_compare(predicate, lhs, rhs, "cmp");
+
+                    Ok(self.to_float(conditional))
+                }
+                Token::And | Token::Or => {
+                    let lhs = self.eval(*left)?;
+                    let lhs = self.truth(lhs);
+
+                    let function = self.current_function();
+                    let lhs_block = self
+                        .builder
+                        .get_insert_block()
+                        .expect("Error when get insert block");
+                    let rhs_block = self.context.append_basic_block(function, "logic.rhs");
+                    let cont_block = self.context.append_basic_block(function, "logic.cont");
+
+                    // `and` only looks at the right side when the left is
+                    // true, `or` only when it is false
+                    let short_circuit = if operator == Token::And {
+                        self.builder
+                            .build_conditional_branch(lhs, rhs_block, cont_block);
+                        0.0
+                    } else {
+                        self.builder
+                            .build_conditional_branch(lhs, cont_block, rhs_block);
+                        1.0
+                    };
+
+                    self.builder.position_at_end(rhs_block);
+                    let rhs = self.eval(*right)?;
+                    let rhs = self.truth(rhs);
+                    let rhs = self.to_float(rhs);
+                    self.builder.build_unconditional_branch(cont_block);
+                    let rhs_block = self
+                        .builder
+                        .get_insert_block()
+                        .expect("Error when get insert block");
+
+                    self.builder.position_at_end(cont_block);
+                    let short_circuit = self.context.f64_type().const_float(short_circuit);
+                    let phi = self.builder.build_phi(self.context.f64_type(), "logic");
+                    phi.add_incoming(&[(&short_circuit, lhs_block), (&rhs, rhs_block)]);
+
+                    Ok(phi.as_basic_value().into_float_value())
+                }
+                Token::ASSIGN => match left.node {
+                    Expression::Variable(var) => {
+                        if !self.locals.contains_key(&var) && Constant::lookup(&var).is_some() {
+                            return Err(HitungError::ConstantAssignment {
+                                name: var,
+                                span: left.span,
+                            });
+                        }
+
+                        let rhs = self.eval(*right)?;
+                        let pointer = match self.locals.get(&var) {
+                            Some(pointer) => *pointer,
+                            None => {
+                                let address = self.environment.declare(&var);
+                                self.slot_pointer(address)
+                            }
+                        };
+                        self.builder.build_store(pointer, rhs);
+
+                        Ok(rhs)
+                    }
+                    _ => Err(HitungError::InvalidAssignment { span: left.span }),
+                },
+                _ => {
+                    let lhs = self.eval(*left)?;
+                    let rhs = self.eval(*right)?;
+
+                    match operator {
+                        Token::Add => Ok(self.builder.build_float_add(lhs, rhs, "add")),
+                        Token::Sub => Ok(self.builder.build_float_sub(lhs, rhs, "sub")),
+                        Token::Mul => Ok(self.builder.build_float_mul(lhs, rhs, "mul")),
+                        Token::Div => Ok(self.builder.build_float_div(lhs, rhs, "div")),
+                        Token::Mod => Ok(self.builder.build_float_rem(lhs, rhs, "rem")),
+                        Token::Pow => Ok(self.call_symbol("llvm.pow.f64", &[lhs, rhs])),
+                        Token::FloorDiv => {
+                            let quotient = self.builder.build_float_div(lhs, rhs, "div");
+                            Ok(self.call_symbol("llvm.floor.f64", &[quotient]))
+                        }
+                        operator => Err(HitungError::UnsupportedOperator { operator, span }),
+                    }
+                }
+            },
+            Expression::Paren(expr) => {
+                let result = &self.eval(*expr)?;
+                Ok(*result)
+            }
+            Expression::Conditional(cond, then, els) => {
+                let if_cond = self.eval(*cond)?;
+                let if_cond = self.truth(if_cond);
+
+                let function = self.current_function();
+
+                let then_block = self.context.append_basic_block(function, "then");
+                let else_block = self.context.append_basic_block(function, "else");
+                let cont_block = self.context.append_basic_block(function, "ifcont");
+
+                self.builder
+                    .build_conditional_branch(if_cond, then_block, else_block);
+                self.builder.position_at_end(then_block);
+
+                let then_value = self.eval(*then)?;
+                self.builder.build_unconditional_branch(cont_block);
+                let then_block = self
+                    .builder
+                    .get_insert_block()
+                    .expect("Error when get insert block");
+
+                // build else block
+                self.builder.position_at_end(else_block);
+                let else_value = self.eval(*els)?;
+                self.builder.build_unconditional_branch(cont_block);
+
+                let else_block = self
+                    .builder
+                    .get_insert_block()
+                    .expect("Error when get insert block");
+                self.builder.position_at_end(cont_block);
+
+                let phi = self.builder.build_phi(self.context.f64_type(), "iftmp");
+                phi.add_incoming(&[(&then_value, then_block), (&else_value, else_block)]);
+
+                Ok(phi.as_basic_value().into_float_value())
+            }
+            Expression::While(cond, body) => {
+                let function = self.current_function();
+                let entry_block = self
+                    .builder
+                    .get_insert_block()
+                    .expect("Error when get insert block");
+
+                let cond_block = self.context.append_basic_block(function, "loop.cond");
+                let body_block = self.context.append_basic_block(function, "loop.body");
+                let end_block = self.context.append_basic_block(function, "loop.end");
+
+                self.builder.build_unconditional_branch(cond_block);
+                self.builder.position_at_end(cond_block);
+
+                // value of the last iteration, `0` when the body never runs
+                let last = self.builder.build_phi(self.context.f64_type(), "looptmp");
+                let zero = self.context.f64_type().const_float(0.0);
+                last.add_incoming(&[(&zero, entry_block)]);
+
+                let loop_cond = self.eval(*cond)?;
+                let loop_cond = self.truth(loop_cond);
+                self.builder
+                    .build_conditional_branch(loop_cond, body_block, end_block);
+
+                self.builder.position_at_end(body_block);
+                let body_value = self.eval(*body)?;
+                self.leave_if_exceeded(end_block);
+                self.builder.build_unconditional_branch(cond_block);
+                let body_block = self
+                    .builder
+                    .get_insert_block()
+                    .expect("Error when get insert block");
+                last.add_incoming(&[(&body_value, body_block)]);
+
+                self.builder.position_at_end(end_block);
+
+                Ok(last.as_basic_value().into_float_value())
+            }
+            Expression::For(name, start, end, step, body) => {
+                let float = self.context.f64_type();
+
+                let start = self.eval(*start)?;
+                let end = self.eval(*end)?;
+                let step = match step {
+                    Some(step) => {
+                        let step_span = step.span;
+                        let step = self.eval(*step)?;
+                        if step.get_constant().is_some_and(|(value, _)| value == 0.0) {
+                            return Err(HitungError::ZeroStep { span: step_span });
+                        }
+                        step
+                    }
+                    None => float.const_float(1.0),
+                };
+
+                let counter = self.entry_alloca(&name);
+                self.builder.build_store(counter, start);
+
+                // count up to `end` with a positive step, down with a negative
+                // one, a step that turns out zero at run time runs nothing
+                let zero = float.const_float(0.0);
+                let up = self
+                    .builder
+                    .build_float_compare(FloatPredicate::OGT, step, zero, "up");
+                let down =
+                    self.builder
+                        .build_float_compare(FloatPredicate::OLT, step, zero, "down");
+
+                let function = self.current_function();
+                let entry_block = self
+                    .builder
+                    .get_insert_block()
+                    .expect("Error when get insert block");
+
+                let cond_block = self.context.append_basic_block(function, "for.cond");
+                let body_block = self.context.append_basic_block(function, "for.body");
+                let end_block = self.context.append_basic_block(function, "for.end");
+
+                self.builder.build_unconditional_branch(cond_block);
+                self.builder.position_at_end(cond_block);
+
+                // value of the last iteration, `0` when the body never runs
+                let last = self.builder.build_phi(float, "fortmp");
+                last.add_incoming(&[(&float.const_float(0.0), entry_block)]);
+
+                let value = self.builder.build_load(counter, &name).into_float_value();
+                let below =
+                    self.builder
+                        .build_float_compare(FloatPredicate::OLE, value, end, "below");
+                let above =
+                    self.builder
+                        .build_float_compare(FloatPredicate::OGE, value, end, "above");
+                let counting_up = self.builder.build_and(up, below, "countup");
+                let counting_down = self.builder.build_and(down, above, "countdown");
+                let in_range = self.builder.build_or(counting_up, counting_down, "inrange");
+                self.builder
+                    .build_conditional_branch(in_range, body_block, end_block);
+
+                // the counter is only visible inside the loop
+                self.builder.position_at_end(body_block);
+                let shadowed = self.locals.insert(name.clone(), counter);
+                let body_value = self.eval(*body);
+                match shadowed {
+                    Some(pointer) => self.locals.insert(name.clone(), pointer),
+                    None => self.locals.remove(&name),
+                };
+                let body_value = body_value?;
+                self.leave_if_exceeded(end_block);
+
+                let value = self.builder.build_load(counter, &name).into_float_value();
+                let next = self.builder.build_float_add(value, step, "next");
+                self.builder.build_store(counter, next);
+                self.builder.build_unconditional_branch(cond_block);
+                let body_block = self
+                    .builder
+                    .get_insert_block()
+                    .expect("Error when get insert block");
+                last.add_incoming(&[(&body_value, body_block)]);
+
+                self.builder.position_at_end(end_block);
+
+                Ok(last.as_basic_value().into_float_value())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::error::ErrorKind;
+    use crate::span::Span;
+
+    #[test]
+    fn test_eval_from_expression() {
+        let expression = Spanned::from(Expression::Binary(
+            Box::new(Spanned::from(Expression::Binary(
+                Box::new(Spanned::from(Expression::from(3))),
+                Token::Add,
+                Box::new(Spanned::from(Expression::from(2))),
+            ))),
+            Token::Sub,
+            Box::new(Spanned::from(Expression::from(2))),
+        ));
+
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let program = Program {
+            functions: vec![],
+            statements: vec![expression],
+        };
+
+        let actual = compiler.jit_compile(program).unwrap();
+        assert_eq!(Some(3.0), actual);
+    }
+
+    #[test]
+    fn test_eval_from_source() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let actual = compiler.compile_source(r"2 + 2 * 3 / 2").unwrap();
+
+        assert_eq!(Some(5.0), actual);
+    }
+
+    #[test]
+    fn test_eval_from_source_if_then_else() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let actual = compiler
+            .compile_source(r"if 1 < 2 then 123 else 456")
+            .unwrap();
+
+        assert_eq!(Some(123.0), actual);
+    }
+
+    #[test]
+    fn test_eval_after_error() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let error = compiler.compile_source(r"1 + x").unwrap_err();
+        assert_eq!(ErrorKind::UndeclaredVariable, error.kind());
+
+        let actual = compiler.compile_source(r"2 + 3").unwrap();
+
+        assert_eq!(Some(5.0), actual);
+    }
+
+    #[test]
+    fn test_variables_persist_across_inputs() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        compiler.compile_source(r"a = 8").unwrap();
+        let actual = compiler.compile_source(r"b = a + 2 * 3").unwrap();
+        assert_eq!(Some(14.0), actual);
+
+        compiler.compile_source(r"a = a + 1").unwrap();
+        assert_eq!(Some(9.0), compiler.variable("a"));
+        assert_eq!(Some(14.0), compiler.variable("b"));
+    }
+
+    #[test]
+    fn test_failed_input_keeps_variables() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        compiler.compile_source(r"a = 1").unwrap();
+        compiler.compile_source(r"(c = 1) + x").unwrap_err();
+
+        assert_eq!(None, compiler.variable("c"));
+        assert_eq!(Some(2.0), compiler.compile_source(r"a + 1").unwrap());
+    }
+
+    #[test]
+    fn test_eval_program() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let actual = compiler
+            .compile_source("a = 8\nb = a + 2 * 3; b - 4\n")
+            .unwrap();
+
+        assert_eq!(Some(10.0), actual);
+        assert_eq!(Some(14.0), compiler.variable("b"));
+    }
+
+    #[test]
+    fn test_eval_empty_program() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        assert_eq!(None, compiler.compile_source("\n").unwrap());
+    }
+
+    #[test]
+    fn test_eval_if_then_else_expressions() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let actual = compiler
+            .compile_source("a = 3; b = 1\nif a + 1 > b * 2 then if a < 2 then 0 else a - 1 else 7")
+            .unwrap();
+
+        assert_eq!(Some(2.0), actual);
+    }
+
+    #[test]
+    fn test_eval_comparison() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let actual = compiler
+            .compile_source(r"(1 < 2) + (2 <= 2) + (3 == 3) + (1 != 1) + (2 >= 3) + (3 > 2)")
+            .unwrap();
+        assert_eq!(Some(4.0), actual);
+
+        let actual = compiler.compile_source(r"a = -1 < 2 * 3").unwrap();
+        assert_eq!(Some(1.0), actual);
+    }
+
+    #[test]
+    fn test_eval_logic() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let actual = compiler
+            .compile_source(r"(1 < 2 and 3) + (0 or 0) + (not 0) + (!(2 > 1)) + (0 || 5 && 6)")
+            .unwrap();
+
+        assert_eq!(Some(3.0), actual);
+    }
+
+    #[test]
+    fn test_eval_logic_nan() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        // NaN is false everywhere, so `not` of it is true
+        let actual = compiler
+            .compile_source(r"n = 0 / 0; (not n) * 100 + (n and 1) * 10 + (n or 0)")
+            .unwrap();
+
+        assert_eq!(Some(100.0), actual);
+    }
+
+    #[test]
+    fn test_eval_logic_short_circuit() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let actual = compiler
+            .compile_source("x = 5\n0 and (x = 1)\n1 or (x = 2)\nx")
+            .unwrap();
+
+        assert_eq!(Some(5.0), actual);
+    }
+
+    #[test]
+    fn test_eval_function_defined_twice() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let source = "fungsi f(x) = x\nfungsi f(x, y) = x";
+        let error = compiler.compile_source(source).unwrap_err();
+        let expected = HitungError::DuplicateFunction {
+            name: "f".to_string(),
+            span: Span::new(23, 1, 2, 8),
+        };
+        assert_eq!(expected, error);
+
+        let error = compiler
+            .compile_source("fungsi g(x) = x\nfungsi g(x) = x * 2\ng(2)")
+            .unwrap_err();
+        assert_eq!(ErrorKind::DuplicateFunction, error.kind());
+
+        // nothing of the failed inputs is left behind
+        let error = compiler.compile_source(r"f(1)").unwrap_err();
+        assert_eq!(ErrorKind::UndeclaredFunction, error.kind());
+
+        // separate inputs still redefine
+        compiler.compile_source(r"fungsi g(x) = x").unwrap();
+        compiler.compile_source(r"fungsi g(x) = x * 2").unwrap();
+        assert_eq!(Some(4.0), compiler.compile_source(r"g(2)").unwrap());
+    }
+
+    #[test]
+    fn test_eval_function() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let defined = compiler
+            .compile_source(r"fungsi luas(p, l) = p * l")
+            .unwrap();
+        assert_eq!(None, defined);
+
+        let actual = compiler.compile_source(r"luas(3, 4) + luas(1, 2)").unwrap();
+        assert_eq!(Some(14.0), actual);
+    }
+
+    #[test]
+    fn test_eval_function_scope() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        // functions are usable before their definition in the same input,
+        // read session variables and their parameters shadow them
+        let source = "x = 10\npajak = 0.5\nharga(4)\nfungsi harga(x) = x * (1 + pajak)";
+        assert_eq!(Some(6.0), compiler.compile_source(source).unwrap());
+        assert_eq!(Some(10.0), compiler.variable("x"));
+
+        compiler.compile_source(r"fungsi harga(x) = x * 2").unwrap();
+        assert_eq!(Some(8.0), compiler.compile_source(r"harga(4)").unwrap());
+
+        // a body reads variables assigned by earlier lines of its input
+        let mut compiler = Compiler::new(&context, false);
+        let source = "pajak = 0.5\nfungsi harga(x) = x * (1 + pajak)\nharga(100)";
+        assert_eq!(Some(150.0), compiler.compile_source(source).unwrap());
+
+        let batch = compiler
+            .compile_batch("diskon = 10\nfungsi net(x) = x - diskon\nnet(x)", &["x"])
+            .unwrap();
+        assert_eq!(vec![90.0], batch.call(&[&[100.0]]));
+    }
+
+    #[test]
+    fn test_eval_function_errors() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        compiler
+            .compile_source(r"fungsi luas(p, l) = p * l")
+            .unwrap();
+
+        let error = compiler.compile_source(r"luas(3)").unwrap_err();
+        assert_eq!(ErrorKind::ArityMismatch, error.kind());
+
+        let error = compiler.compile_source(r"keliling(3)").unwrap_err();
+        assert_eq!(ErrorKind::UndeclaredFunction, error.kind());
+
+        let error = compiler.compile_source(r"fungsi f(a, a) = a").unwrap_err();
+        assert_eq!(ErrorKind::DuplicateParameter, error.kind());
+
+        // a failed definition does not replace the working one
+        let error = compiler
+            .compile_source(r"fungsi luas(p, l) = p * x")
+            .unwrap_err();
+        assert_eq!(ErrorKind::UndeclaredVariable, error.kind());
+        assert_eq!(Some(12.0), compiler.compile_source(r"luas(3, 4)").unwrap());
+    }
+
+    #[test]
+    fn test_eval_recursion() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let source = "fungsi fakt(n) = if n < 2 then 1 else n * fakt(n - 1)\nfakt(5)";
+        assert_eq!(Some(120.0), compiler.compile_source(source).unwrap());
+
+        let source = "fungsi genap(n) = if n == 0 then 1 else ganjil(n - 1)
+fungsi ganjil(n) = if n == 0 then 0 else genap(n - 1)
+genap(10) + ganjil(7)";
+        assert_eq!(Some(2.0), compiler.compile_source(source).unwrap());
+    }
+
+    #[test]
+    fn test_eval_recursion_limit() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+        compiler.set_recursion_limit(100);
+
+        compiler
+            .compile_source(r"fungsi turun(n) = if n == 0 then 0 else 1 + turun(n - 1)")
+            .unwrap();
+        assert_eq!(Some(99.0), compiler.compile_source(r"turun(99)").unwrap());
+
+        let error = compiler.compile_source(r"turun(100)").unwrap_err();
+        assert_eq!(ErrorKind::RecursionLimit, error.kind());
+
+        // the guard is reset for the next input
+        assert_eq!(Some(3.0), compiler.compile_source(r"turun(3)").unwrap());
+
+        let error = compiler.compile_source(r"fungsi terus(n) = terus(n + 1); terus(0)");
+        assert_eq!(ErrorKind::RecursionLimit, error.unwrap_err().kind());
+    }
+
+    #[test]
+    fn test_eval_recursion_limit_in_loop() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+        compiler.set_recursion_limit(100);
+        compiler
+            .compile_source(r"fungsi f(n) = jika n == 0 maka 1 selain f(n - 1)")
+            .unwrap();
+
+        // past the limit `f` returns 0, the loops must still end
+        let error = compiler.compile_source(r"selama f(1000) == 0 lakukan 1");
+        assert_eq!(ErrorKind::RecursionLimit, error.unwrap_err().kind());
+
+        let error = compiler.compile_source(r"untuk i dari 1 sampai inf lakukan f(1000)");
+        assert_eq!(ErrorKind::RecursionLimit, error.unwrap_err().kind());
+
+        let error = compiler.compile_source(r"fungsi g(n) = selama f(n) == 0 lakukan 1; g(1000)");
+        assert_eq!(ErrorKind::RecursionLimit, error.unwrap_err().kind());
+
+        assert_eq!(Some(1.0), compiler.compile_source(r"f(3)").unwrap());
+    }
+
+    #[test]
+    fn test_eval_while() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let source = "i = 0; total = 0
+selama i < 4 lakukan total = total + (i = i + 1)";
+        assert_eq!(Some(10.0), compiler.compile_source(source).unwrap());
+        assert_eq!(Some(4.0), compiler.variable("i"));
+        assert_eq!(Some(10.0), compiler.variable("total"));
+
+        // the body never runs
+        let actual = compiler
+            .compile_source(r"selama i < 0 lakukan i = 1")
+            .unwrap();
+        assert_eq!(Some(0.0), actual);
+    }
+
+    #[test]
+    fn test_eval_while_in_function() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        // assignment to a parameter only changes the parameter
+        let source = "n = 7
+fungsi mundur(n) = selama n > 2 lakukan n = n - 1
+mundur(5)";
+        assert_eq!(Some(2.0), compiler.compile_source(source).unwrap());
+        assert_eq!(Some(7.0), compiler.variable("n"));
+    }
+
+    #[test]
+    fn test_eval_for() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let source = "total = 0\nuntuk i dari 1 sampai 10 lakukan total = total + i";
+        assert_eq!(Some(55.0), compiler.compile_source(source).unwrap());
+        assert_eq!(Some(55.0), compiler.variable("total"));
+
+        // the counter is scoped to the loop
+        assert_eq!(None, compiler.variable("i"));
+        let error = compiler.compile_source(r"i").unwrap_err();
+        assert_eq!(ErrorKind::UndeclaredVariable, error.kind());
+    }
+
+    #[test]
+    fn test_eval_for_step() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let source = "n = 0\nuntuk i dari 1 sampai 10 langkah 3 lakukan n = n + i";
+        assert_eq!(Some(22.0), compiler.compile_source(source).unwrap());
+
+        let source = "n = 0\nuntuk i dari 5 sampai 1 langkah -2 lakukan n = n * 10 + i";
+        assert_eq!(Some(531.0), compiler.compile_source(source).unwrap());
+
+        let source = r"untuk i dari 5 sampai 1 lakukan i";
+        assert_eq!(Some(0.0), compiler.compile_source(source).unwrap());
+    }
+
+    #[test]
+    fn test_eval_for_zero_step() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let error = compiler
+            .compile_source(r"untuk i dari 1 sampai 1 langkah 0 lakukan i")
+            .unwrap_err();
+        let expected = HitungError::ZeroStep {
+            span: Span::new(32, 1, 1, 33),
+        };
+        assert_eq!(expected, error);
+
+        let error = compiler
+            .compile_source(r"untuk i dari 1 sampai 1 langkah 1 - 1 lakukan i")
+            .unwrap_err();
+        assert_eq!(ErrorKind::ZeroStep, error.kind());
+
+        // only known when it runs, the body is skipped
+        let source = "s = 0\nuntuk i dari 1 sampai 1 langkah s lakukan i";
+        assert_eq!(Some(0.0), compiler.compile_source(source).unwrap());
+    }
+
+    #[test]
+    fn test_eval_for_nested_and_shadowing() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let source = "i = 100; n = 0
+untuk i dari 1 sampai 3 lakukan untuk j dari 1 sampai i lakukan n = n + 1
+fungsi jumlah(n) = (s = 0) + untuk k dari 1 sampai n lakukan s = s + k
+n + i + jumlah(4)";
+        assert_eq!(Some(116.0), compiler.compile_source(source).unwrap());
+    }
+
+    #[test]
+    fn test_eval_power_remainder_floor_division() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let mut eval = |source: &str| compiler.compile_source(source).unwrap();
+
+        assert_eq!(Some(512.0), eval(r"2 ^ 3 ^ 2"));
+        assert_eq!(Some(-4.0), eval(r"-2 ** 2"));
+        assert_eq!(Some(0.5), eval(r"2 ^ -1"));
+        assert_eq!(Some(1.0), eval(r"7 % 3"));
+        assert_eq!(Some(-1.0), eval(r"-7 % 3"));
+        assert_eq!(Some(3.0), eval(r"7 // 2"));
+        assert_eq!(Some(-4.0), eval(r"-7 // 2"));
+        assert_eq!(Some(19.0), eval(r"1 + 2 * 3 ^ 2"));
+    }
+
+    #[test]
+    fn test_eval_builtins() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let mut eval = |source: &str| compiler.compile_source(source).unwrap().unwrap();
+
+        assert_eq!(3.0, eval(r"sqrt(9)"));
+        assert_eq!(0.0, eval(r"sin(0)"));
+        assert_eq!(1.0, eval(r"cos(0)"));
+        assert!((eval(r"tan(1)") - 1.0_f64.tan()).abs() < 1e-12);
+        assert_eq!(3.0, eval(r"log(1000)"));
+        assert_eq!(0.0, eval(r"ln(1)"));
+        assert_eq!(1.0, eval(r"exp(0)"));
+        assert_eq!(2.5, eval(r"abs(-2.5)"));
+        assert_eq!(-3.0, eval(r"floor(-2.5)"));
+        assert_eq!(-2.0, eval(r"ceil(-2.5)"));
+        assert_eq!(3.0, eval(r"round(2.5)"));
+        assert_eq!(2.0, eval(r"min(2, 7)"));
+        assert_eq!(7.0, eval(r"max(2, sqrt(49))"));
+    }
+
+    #[test]
+    fn test_eval_builtin_errors() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let error = compiler.compile_source(r"max(1)").unwrap_err();
+        assert_eq!(
+            HitungError::ArityMismatch {
+                name: "max".to_string(),
+                expected: 2,
+                found: 1,
+                span: Span::new(0, 6, 1, 1),
+            },
+            error
+        );
+
+        // a user function hides the builtin
+        compiler.compile_source(r"fungsi abs(x) = x").unwrap();
+        assert_eq!(Some(-1.0), compiler.compile_source(r"abs(-1)").unwrap());
+    }
+
+    #[test]
+    fn test_eval_constants() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let actual = compiler.compile_source(r"tau / pi").unwrap();
+        assert_eq!(Some(2.0), actual);
+
+        let actual = compiler.compile_source(r"ln(e) + (inf > 1e308)").unwrap();
+        assert_eq!(Some(2.0), actual);
+
+        let actual = compiler.compile_source(r"nan").unwrap().unwrap();
+        assert!(actual.is_nan());
+
+        // a parameter may still be called `e`
+        let source = "fungsi f(e) = e * 2\nf(3)";
+        assert_eq!(Some(6.0), compiler.compile_source(source).unwrap());
+    }
+
+    #[test]
+    fn test_assign_constant() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let error = compiler.compile_source(r"a = 1; pi = 3").unwrap_err();
+        assert_eq!(
+            HitungError::ConstantAssignment {
+                name: "pi".to_string(),
+                span: Span::new(7, 2, 1, 8),
+            },
+            error
+        );
+        assert_eq!(None, compiler.variable("pi"));
+    }
+
+    #[test]
+    fn test_register_fn() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let rate = 0.11;
+        compiler.register_fn("pajak", move |x: f64| x * rate);
+        compiler.register_fn("bulatkan", |x: f64, step: f64| (x / step).round() * step);
+        compiler.register_fn("kurs", || 15_000.0);
+
+        let actual = compiler
+            .compile_source(r"bulatkan(pajak(1234) + 100, 50)")
+            .unwrap();
+        assert_eq!(Some(250.0), actual);
+
+        let source = "fungsi harga(x) = x + pajak(x)\nharga(100) * kurs()";
+        assert_eq!(Some(1_665_000.0), compiler.compile_source(source).unwrap());
+
+        let error = compiler.compile_source(r"pajak(1, 2)").unwrap_err();
+        assert_eq!(ErrorKind::ArityMismatch, error.kind());
+
+        // registering again replaces it for new code, compiled code keeps
+        // calling the old closure
+        compiler.register_fn("pajak", |x: f64| x * 0.5);
+        assert_eq!(Some(5.0), compiler.compile_source(r"pajak(10)").unwrap());
+        assert_eq!(Some(111.0), compiler.compile_source(r"harga(100)").unwrap());
+    }
+
+    #[test]
+    fn test_compile_fn() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let harga = compiler
+            .compile_fn(
+                r"harga * (1 + pajak) - diskon",
+                &["harga", "pajak", "diskon"],
+            )
+            .unwrap();
+
+        assert_eq!(["harga", "pajak", "diskon"], harga.params());
+        assert_eq!(100.0, harga.call(&[100.0, 0.1, 10.0]));
+        assert_eq!(215.0, harga.call(&[200.0, 0.1, 5.0]));
+        let total: f64 = (0..1000).map(|i| harga.call(&[i as f64, 0.0, 0.0])).sum();
+        assert_eq!(499_500.0, total);
+    }
+
+    #[test]
+    fn test_compile_fn_scope() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+        compiler.compile_source(r"x = 100").unwrap();
+        compiler
+            .compile_source(r"fungsi kuadrat(x) = x * x")
+            .unwrap();
+
+        // parameters hide variables, assigning to one leaves the arguments alone
+        let formula = compiler
+            .compile_fn("x = x + 1\nkuadrat(x) + e", &["x", "e"])
+            .unwrap();
+        let args = [2.0, 1.0];
+        assert_eq!(10.0, formula.call(&args));
+        assert_eq!([2.0, 1.0], args);
+        assert_eq!(Some(100.0), compiler.variable("x"));
+
+        // the handle outlives the compiler
+        compiler.compile_source(r"total = 0").unwrap();
+        let total = compiler.compile_fn(r"total = total + x", &["x"]).unwrap();
+        drop(compiler);
+        assert_eq!(1.0, total.call(&[1.0]));
+        assert_eq!(3.0, total.call(&[2.0]));
+    }
+
+    #[test]
+    fn test_compile_fn_error() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let error = compiler.compile_fn(r"a + b", &["a", "a"]).unwrap_err();
+        assert_eq!(ErrorKind::DuplicateParameter, error.kind());
+
+        let error = compiler.compile_fn(r"a + c", &["a", "b"]).unwrap_err();
+        assert_eq!(ErrorKind::UndeclaredVariable, error.kind());
+
+        let error = compiler.compile_fn("", &["a"]).unwrap_err();
+        assert_eq!(ErrorKind::UnexpectedEof, error.kind());
+
+        let formula = compiler.compile_fn(r"a + b", &["a", "b"]).unwrap();
+        assert_eq!(3.0, formula.call(&[1.0, 2.0]));
+    }
+
+    #[test]
+    #[should_panic(expected = "formula takes 2 argument(s)")]
+    fn test_compile_fn_arity() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let formula = compiler.compile_fn(r"a + b", &["a", "b"]).unwrap();
+        formula.call(&[1.0]);
+    }
+
+    #[test]
+    fn test_compile_batch() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let harga = compiler
+            .compile_batch(
+                r"harga * (1 + pajak) - diskon",
+                &["harga", "pajak", "diskon"],
+            )
+            .unwrap();
+
+        let actual = harga.call(&[&[100.0, 200.0], &[0.1, 0.1], &[10.0, 5.0]]);
+        assert_eq!(vec![100.0, 215.0], actual);
+        assert_eq!(Vec::<f64>::new(), harga.call(&[&[], &[], &[]]));
+
+        // long enough for the vector loop and the rows left after it
+        let x: Vec<f64> = (0..1003).map(f64::from).collect();
+        let kuadrat = compiler
+            .compile_batch(r"jika x % 2 == 0 maka x * x selain sqrt(x)", &["x"])
+            .unwrap();
+        let expected: Vec<f64> = x
+            .iter()
+            .map(|x| if x % 2.0 == 0.0 { x * x } else { x.sqrt() })
+            .collect();
+        assert_eq!(expected, kuadrat.call(&[&x]));
+    }
+
+    #[test]
+    fn test_compile_batch_functions() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+        compiler
+            .compile_source(r"fungsi fak(n) = jika n <= 1 maka 1 selain n * fak(n - 1)")
+            .unwrap();
+
+        let batch = compiler.compile_batch(r"fak(n)", &["n"]).unwrap();
+        assert_eq!(vec![1.0, 6.0, 120.0], batch.call(&[&[1.0, 3.0, 5.0]]));
+
+        compiler.set_recursion_limit(10);
+        let actual = batch.call(&[&[3.0, 50.0]]);
+        assert!(actual.iter().all(|value| value.is_nan()));
+        assert_eq!(vec![6.0], batch.call(&[&[3.0]]));
+    }
+
+    #[test]
+    #[should_panic(expected = "every column must have the same length")]
+    fn test_compile_batch_length() {
+        let context = Context::create();
+        let mut compiler = Compiler::new(&context, false);
+
+        let batch = compiler.compile_batch(r"a + b", &["a", "b"]).unwrap();
+        batch.call(&[&[1.0, 2.0], &[1.0]]);
+    }
+
+    #[test]
+    fn test_compile_statements() {
         let context = Context::create();
         let mut compiler = Compiler::new(&context, false);
 
-        let actual = compiler.compile_source(r"if 1 < 2 then 123 else 456").unwrap();
+        let actual = compiler
+            .compile_statements("a = 8\nfungsi f(x) = x * 2\nf(a); a + 1")
+            .unwrap();
+        assert_eq!(vec![8.0, 16.0, 9.0], actual);
 
-        assert_eq!(123.0, actual);
+        let actual = compiler.compile_statements("fungsi g(x) = x").unwrap();
+        assert_eq!(Vec::<f64>::new(), actual);
     }
 }
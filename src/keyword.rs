@@ -0,0 +1,149 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::message::{CliError, CliMessage, Language};
+use crate::token::Token;
+
+/// Which spelling of the keywords a session accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    Indonesian,
+    English,
+    #[default]
+    Both,
+}
+
+/// Every keyword with its Indonesian and English spelling.
+const KEYWORDS: &[(Token, &str, &str)] = &[
+    (Token::If, "jika", "if"),
+    (Token::Then, "maka", "then"),
+    (Token::Else, "selain", "else"),
+    (Token::And, "dan", "and"),
+    (Token::Or, "atau", "or"),
+    (Token::Not, "bukan", "not"),
+    (Token::Function, "fungsi", "function"),
+    (Token::While, "selama", "while"),
+    (Token::For, "untuk", "for"),
+    (Token::From, "dari", "from"),
+    (Token::To, "sampai", "to"),
+    (Token::Step, "langkah", "step"),
+    (Token::Do, "lakukan", "do"),
+];
+
+impl Dialect {
+    /// The keyword `word` stands for, `None` when it is an identifier.
+    pub fn keyword(self, word: &str) -> Option<Token> {
+        KEYWORDS
+            .iter()
+            .find(|(_, indonesian, english)| match self {
+                Dialect::Indonesian => *indonesian == word,
+                Dialect::English => *english == word,
+                Dialect::Both => *indonesian == word || *english == word,
+            })
+            .map(|(token, _, _)| token.clone())
+    }
+
+    /// `token` the way it is written in this dialect, e.g. `maka` for
+    /// [`Token::Then`] in Indonesian.
+    pub fn spell(self, token: &Token) -> String {
+        let keyword = KEYWORDS.iter().find(|(keyword, _, _)| keyword == token);
+
+        match (self, keyword) {
+            (Dialect::Indonesian, Some((_, indonesian, _))) => indonesian.to_string(),
+            (Dialect::English, Some((_, _, english))) => english.to_string(),
+            _ => token.to_string(),
+        }
+    }
+
+    /// The one spelling messages in `language` use, under [`Dialect::Both`]
+    /// keywords follow the language so a message never mixes `then` with
+    /// `selain`.
+    pub fn for_language(self, language: Language) -> Dialect {
+        match (self, language) {
+            (Dialect::Both, Language::English) => Dialect::English,
+            (Dialect::Both, Language::Indonesian) => Dialect::Indonesian,
+            (dialect, _) => dialect,
+        }
+    }
+
+    /// Spelling of every keyword keyed by its English name, for filling in
+    /// the `{then}` placeholders of the message catalog.
+    pub fn placeholders(self) -> Vec<(&'static str, String)> {
+        KEYWORDS
+            .iter()
+            .map(|(token, _, english)| (*english, self.spell(token)))
+            .collect()
+    }
+}
+
+impl FromStr for Dialect {
+    type Err = CliError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "id" => Ok(Dialect::Indonesian),
+            "en" => Ok(Dialect::English),
+            "both" => Ok(Dialect::Both),
+            _ => Err(CliError::new(CliMessage::UnknownKeywords).with("name", name)),
+        }
+    }
+}
+
+impl fmt::Display for Dialect {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Dialect::Indonesian => write!(f, "id"),
+            Dialect::English => write!(f, "en"),
+            Dialect::Both => write!(f, "both"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_keyword() {
+        assert_eq!(Some(Token::If), Dialect::Indonesian.keyword("jika"));
+        assert_eq!(None, Dialect::Indonesian.keyword("if"));
+
+        assert_eq!(Some(Token::Then), Dialect::English.keyword("then"));
+        assert_eq!(None, Dialect::English.keyword("maka"));
+
+        assert_eq!(Some(Token::Else), Dialect::Both.keyword("selain"));
+        assert_eq!(Some(Token::Else), Dialect::Both.keyword("else"));
+        assert_eq!(None, Dialect::Both.keyword("luas"));
+    }
+
+    #[test]
+    fn test_spell() {
+        assert_eq!("maka", Dialect::Indonesian.spell(&Token::Then));
+        assert_eq!("function", Dialect::English.spell(&Token::Function));
+        assert_eq!("then", Dialect::Both.spell(&Token::Then));
+        assert_eq!("*", Dialect::Indonesian.spell(&Token::Mul));
+    }
+
+    #[test]
+    fn test_for_language() {
+        assert_eq!(
+            Dialect::English,
+            Dialect::Both.for_language(Language::English)
+        );
+        assert_eq!(
+            Dialect::Indonesian,
+            Dialect::Both.for_language(Language::Indonesian)
+        );
+        assert_eq!(
+            Dialect::Indonesian,
+            Dialect::Indonesian.for_language(Language::English)
+        );
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!(Ok(Dialect::Indonesian), "id".parse());
+        assert_eq!(Ok(Dialect::Both), "both".parse());
+        assert!("jawa".parse::<Dialect>().is_err());
+    }
+}
@@ -1,25 +1,51 @@
 use std::iter::Peekable;
 use std::str::Chars;
 
+use crate::error::{HitungError, Result};
+use crate::keyword::Dialect;
+use crate::span::{Span, Spanned};
 use crate::token::Token;
 
 #[derive(Debug)]
 pub struct Lexer<'a> {
     input: Peekable<Chars<'a>>,
+    offset: usize,
+    line: usize,
+    column: usize,
+    dialect: Dialect,
+    // return comments as tokens instead of skipping them
+    comments: bool,
 }
 
 impl<'a> Lexer<'a> {
-    pub fn new(input: &str) -> Lexer {
+    pub fn new(input: &str) -> Lexer<'_> {
+        Lexer::with_dialect(input, Dialect::default())
+    }
+
+    /// Lexer that only knows the keywords of `dialect`.
+    pub fn with_dialect(input: &str, dialect: Dialect) -> Lexer<'_> {
         Lexer {
             input: input.chars().peekable(),
+            offset: 0,
+            line: 1,
+            column: 1,
+            dialect,
+            comments: false,
         }
     }
 
-    pub fn lex(mut self) -> Vec<Token> {
+    /// Keep comments as [`Token::Comment`] trivia, e.g. for a formatter that
+    /// has to preserve them.
+    pub fn keep_comments(mut self) -> Self {
+        self.comments = true;
+        self
+    }
+
+    pub fn lex(mut self) -> Result<Vec<Spanned<Token>>> {
         let mut tokens = vec![];
         loop {
-            let current_token = self.next_token();
-            if current_token == Token::EOF || current_token == Token::ILLEGAL {
+            let current_token = self.next_token()?;
+            if current_token.node == Token::EOF {
                 tokens.push(current_token);
                 break;
             } else {
@@ -27,127 +53,308 @@ impl<'a> Lexer<'a> {
             }
         }
 
-        tokens
+        Ok(tokens)
     }
 
-    pub fn next_token(&mut self) -> Token {
-        match self.input.peek() {
+    pub fn next_token(&mut self) -> Result<Spanned<Token>> {
+        loop {
+            self.skip_whitespace();
+
+            let start = self.position();
+            let token = self.read_token(start)?;
+
+            let token = match token {
+                Token::Comment(_) if self.comments => token,
+                // a block comment over several lines still ends the statement
+                Token::Comment(text) if text.contains('\n') => Token::Newline,
+                Token::Comment(_) => continue,
+                token => token,
+            };
+
+            return Ok(Spanned::new(token, self.span_from(start)));
+        }
+    }
+
+    fn read_token(&mut self, start: Span) -> Result<Token> {
+        let token = match self.input.peek() {
             Some(ch) => match ch {
-                ' ' | '\t' => {
-                    self.input.next();
-                    self.next_token()
-                }
-                '\n' => {
-                    self.input.next();
-                    self.next_token()
-                }
-                ch if ch.is_numeric() => self.read_numeric(),
+                ch if ch.is_ascii_digit() => return self.read_numeric(start),
                 '+' => {
-                    self.input.next();
+                    self.bump();
                     Token::Add
                 }
                 '-' => {
-                    self.input.next();
+                    self.bump();
                     Token::Sub
                 }
                 '*' => {
-                    self.input.next();
-                    Token::Mul
+                    self.bump();
+                    match self.input.peek() {
+                        Some('*') => {
+                            self.bump();
+                            Token::Pow
+                        }
+                        _ => Token::Mul,
+                    }
+                }
+                '^' => {
+                    self.bump();
+                    Token::Pow
+                }
+                '%' => {
+                    self.bump();
+                    Token::Mod
                 }
                 '/' => {
-                    self.input.next();
-                    Token::Div
+                    self.bump();
+                    match self.input.peek() {
+                        // floor division, `//` no longer starts a comment
+                        Some('/') => {
+                            self.bump();
+                            Token::FloorDiv
+                        }
+                        Some('*') => self.block_comment(start)?,
+                        _ => Token::Div,
+                    }
                 }
+                '#' => self.line_comment(),
                 '(' => {
-                    self.input.next();
+                    self.bump();
                     Token::LParen
                 }
                 ')' => {
-                    self.input.next();
+                    self.bump();
                     Token::RParen
                 }
-                ch if ch.is_alphabetic() => self.read_identifier(),
+                '\n' => {
+                    self.bump();
+                    Token::Newline
+                }
+                ';' => {
+                    self.bump();
+                    Token::Semicolon
+                }
+                ',' => {
+                    self.bump();
+                    Token::Comma
+                }
+                ch if ch.is_alphabetic() || *ch == '_' => self.read_identifier(),
                 '=' => {
-                    self.input.next();
-                    match self.input.peek() {
-                        Some(c) => {
-                            if *c == '=' {
-                                self.input.next();
-                                Token::EQ
-                            } else {
-                                self.input.next();
-                                Token::ASSIGN
-                            }
-                        }
-                        _ => self.next_token(),
-                    }
+                    self.bump();
+                    self.followed_by_eq(Token::EQ, Token::ASSIGN)
                 }
                 '<' => {
-                    self.input.next();
-                    Token::LT
+                    self.bump();
+                    self.followed_by_eq(Token::LTE, Token::LT)
                 }
                 '>' => {
-                    self.input.next();
-                    Token::GT
+                    self.bump();
+                    self.followed_by_eq(Token::GTE, Token::GT)
+                }
+                '!' => {
+                    self.bump();
+                    self.followed_by_eq(Token::NEQ, Token::Not)
+                }
+                '&' => {
+                    self.bump();
+                    self.doubled('&', Token::And, start)?
+                }
+                '|' => {
+                    self.bump();
+                    self.doubled('|', Token::Or, start)?
                 }
                 _ => {
-                    self.input.next();
-                    Token::ILLEGAL
+                    let character = self.bump().unwrap_or_default();
+                    return Err(HitungError::IllegalCharacter {
+                        character,
+                        span: self.span_from(start),
+                    });
                 }
             },
             None => Token::EOF,
+        };
+
+        Ok(token)
+    }
+
+    /// `#` comment up to the end of the line, the newline itself is left for
+    /// the next token.
+    fn line_comment(&mut self) -> Token {
+        let mut text = String::new();
+
+        while let Some(&ch) = self.input.peek() {
+            if ch == '\n' {
+                break;
+            }
+            text.push(ch);
+            self.bump();
         }
+
+        Token::Comment(text)
     }
 
-    fn read_numeric(&mut self) -> Token {
-        let mut literal = String::new();
+    /// `/* ... */` comment, the `/` is already consumed. Block comments nest,
+    /// so a piece of code that has one can still be commented out.
+    fn block_comment(&mut self, start: Span) -> Result<Token> {
+        let mut text = "/".to_string();
+        text.extend(self.bump());
 
-        loop {
-            match self.input.peek() {
-                Some(&ch) => {
-                    if ch.is_numeric() || ch == '.' {
-                        literal.push(ch);
-                        self.input.next();
-                    } else {
-                        break;
-                    }
+        let mut depth = 1;
+        // last character that can still start a `/*` or `*/`
+        let mut previous = None;
+        while depth > 0 {
+            let ch = self.bump().ok_or(HitungError::UnterminatedComment {
+                span: Span { len: 2, ..start },
+            })?;
+            text.push(ch);
+
+            match (previous, ch) {
+                (Some('/'), '*') => {
+                    depth += 1;
+                    previous = None;
+                }
+                (Some('*'), '/') => {
+                    depth -= 1;
+                    previous = None;
                 }
-                _ => break,
+                _ => previous = Some(ch),
             }
         }
 
-        match literal.parse() {
-            Ok(l) => Token::Num(l),
-            Err(_e) => Token::ILLEGAL,
+        Ok(Token::Comment(text))
+    }
+
+    /// `with_eq` when the next character is `=`, which is then consumed.
+    fn followed_by_eq(&mut self, with_eq: Token, without: Token) -> Token {
+        match self.input.peek() {
+            Some('=') => {
+                self.bump();
+                with_eq
+            }
+            _ => without,
         }
     }
 
-    fn read_identifier(&mut self) -> Token {
+    /// `token` when the character just consumed is repeated, like `&&`.
+    fn doubled(&mut self, character: char, token: Token, start: Span) -> Result<Token> {
+        if self.input.peek() == Some(&character) {
+            self.bump();
+            Ok(token)
+        } else {
+            Err(HitungError::IllegalCharacter {
+                character,
+                span: self.span_from(start),
+            })
+        }
+    }
+
+    /// Consume one character, keeping the byte offset, line and column in sync.
+    fn bump(&mut self) -> Option<char> {
+        let ch = self.input.next()?;
+
+        self.offset += ch.len_utf8();
+        if ch == '\n' {
+            self.line += 1;
+            self.column = 1;
+        } else {
+            self.column += 1;
+        }
+
+        Some(ch)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ') | Some('\t') | Some('\r') = self.input.peek() {
+            self.bump();
+        }
+    }
+
+    fn position(&self) -> Span {
+        Span::new(self.offset, 0, self.line, self.column)
+    }
+
+    fn span_from(&self, start: Span) -> Span {
+        Span {
+            len: self.offset - start.offset,
+            ..start
+        }
+    }
+
+    /// Number literal like `42`, `1.5`, `1e-3`, `0xFF`, `0b1010` or
+    /// `1_000_000`. Everything that could belong to the literal is consumed
+    /// first, so `1.2.3` or `0xG` are reported as a whole.
+    fn read_numeric(&mut self, start: Span) -> Result<Token> {
         let mut literal = String::new();
 
-        loop {
-            match self.input.peek() {
-                Some(&ch) => {
-                    if !ch.is_alphabetic() {
-                        break;
-                    }
-                    if ch.is_ascii_whitespace() {
-                        // self.input.next();
-                        break;
-                    };
-                    literal.push(ch);
-                    self.input.next();
-                }
-                _ => break,
+        while let Some(&ch) = self.input.peek() {
+            let exponent_sign = (ch == '+' || ch == '-')
+                && literal.ends_with(['e', 'E'])
+                && !literal.starts_with("0x")
+                && !literal.starts_with("0X");
+            if !(ch.is_alphanumeric() || ch == '_' || ch == '.' || exponent_sign) {
+                break;
             }
+            literal.push(ch);
+            self.bump();
+        }
+
+        match parse_number(&literal) {
+            Some(number) => Ok(Token::Num(number)),
+            None => Err(HitungError::MalformedNumber {
+                literal,
+                span: self.span_from(start),
+            }),
         }
+    }
 
-        match literal.as_str() {
-            "if" => Token::If,
-            "then" => Token::Then,
-            "else" => Token::Else,
-            _ => Token::IDENTIFIER(literal),
+    /// A letter or `_` followed by letters, digits and `_`, any Unicode
+    /// letter counts.
+    fn read_identifier(&mut self) -> Token {
+        let mut literal = String::new();
+
+        while let Some(&ch) = self.input.peek() {
+            if !(ch.is_alphanumeric() || ch == '_') {
+                break;
+            }
+            literal.push(ch);
+            self.bump();
         }
+
+        self.dialect
+            .keyword(&literal)
+            .unwrap_or(Token::IDENTIFIER(literal))
+    }
+}
+
+fn parse_number(literal: &str) -> Option<f64> {
+    let (digits, radix) = match literal.get(..2) {
+        Some("0x" | "0X") => (&literal[2..], 16),
+        Some("0o" | "0O") => (&literal[2..], 8),
+        Some("0b" | "0B") => (&literal[2..], 2),
+        _ => (literal, 10),
+    };
+
+    // `_` only between two digits, so `1__0`, `1_` and `1_.5` are rejected
+    let chars: Vec<char> = digits.chars().collect();
+    let separators_ok = chars.iter().enumerate().all(|(index, &ch)| {
+        ch != '_'
+            || (index > 0
+                && chars[index - 1].is_digit(radix)
+                && chars
+                    .get(index + 1)
+                    .is_some_and(|next| next.is_digit(radix)))
+    });
+    if digits.is_empty() || !separators_ok {
+        return None;
+    }
+
+    let digits = digits.replace('_', "");
+    if radix == 10 {
+        digits.parse().ok()
+    } else {
+        u64::from_str_radix(&digits, radix)
+            .ok()
+            .map(|number| number as f64)
     }
 }
 
@@ -155,10 +362,18 @@ impl<'a> Lexer<'a> {
 mod test {
     use super::*;
 
+    fn lex(source: &str) -> Vec<Token> {
+        Lexer::new(source)
+            .lex()
+            .unwrap()
+            .into_iter()
+            .map(|token| token.node)
+            .collect()
+    }
+
     #[test]
     fn test_num() {
-        let lexer = Lexer::new(r#"32"#);
-        let tokens = lexer.lex();
+        let tokens = lex(r#"32"#);
 
         let expected = vec![Token::from(32), Token::EOF];
         assert_eq!(expected, tokens);
@@ -166,8 +381,7 @@ mod test {
 
     #[test]
     fn test_num_float() {
-        let lexer = Lexer::new(r#"32.5"#);
-        let tokens = lexer.lex();
+        let tokens = lex(r#"32.5"#);
 
         let expected = vec![Token::Num(32.5), Token::EOF];
         assert_eq!(expected, tokens);
@@ -175,8 +389,7 @@ mod test {
 
     #[test]
     fn test_num_whitespace() {
-        let lexer = Lexer::new(r#"32 2"#);
-        let tokens = lexer.lex();
+        let tokens = lex(r#"32 2"#);
 
         let expected = vec![Token::from(32), Token::from(2), Token::EOF];
 
@@ -185,18 +398,40 @@ mod test {
 
     #[test]
     fn test_num_operator() {
-        let lexer = Lexer::new(r#"-+/*"#);
-        let tokens = lexer.lex();
+        let tokens = lex(r#"-+/ *"#);
 
         let expected = vec![Token::Sub, Token::Add, Token::Div, Token::Mul, Token::EOF];
 
         assert_eq!(expected, tokens);
     }
 
+    #[test]
+    fn test_power_and_remainder() {
+        let tokens = lex("2 ** 3 ^ 2 % 5 // 2 / 1 * 1");
+
+        let expected = vec![
+            Token::from(2),
+            Token::Pow,
+            Token::from(3),
+            Token::Pow,
+            Token::from(2),
+            Token::Mod,
+            Token::from(5),
+            Token::FloorDiv,
+            Token::from(2),
+            Token::Div,
+            Token::from(1),
+            Token::Mul,
+            Token::from(1),
+            Token::EOF,
+        ];
+
+        assert_eq!(expected, tokens);
+    }
+
     #[test]
     fn test_assignment() {
-        let lexer = Lexer::new(r#"a = 123"#);
-        let tokens = lexer.lex();
+        let tokens = lex(r#"a = 123"#);
 
         let expected = vec![
             Token::IDENTIFIER("a".to_string()),
@@ -210,8 +445,7 @@ mod test {
 
     #[test]
     fn test_conditional() {
-        let lexer = Lexer::new(r#"if 1 < 2 then 1 else 0"#);
-        let tokens = lexer.lex();
+        let tokens = lex(r#"if 1 < 2 then 1 else 0"#);
 
         let expected = vec![
             Token::If,
@@ -227,4 +461,360 @@ mod test {
 
         assert_eq!(expected, tokens);
     }
+
+    #[test]
+    fn test_comparison() {
+        let tokens = lex("< <= > >= == !=");
+
+        let expected = vec![
+            Token::LT,
+            Token::LTE,
+            Token::GT,
+            Token::GTE,
+            Token::EQ,
+            Token::NEQ,
+            Token::EOF,
+        ];
+
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_logic() {
+        let tokens = lex("a and b or not c && d || !e");
+
+        let expected = vec![
+            Token::IDENTIFIER("a".to_string()),
+            Token::And,
+            Token::IDENTIFIER("b".to_string()),
+            Token::Or,
+            Token::Not,
+            Token::IDENTIFIER("c".to_string()),
+            Token::And,
+            Token::IDENTIFIER("d".to_string()),
+            Token::Or,
+            Token::Not,
+            Token::IDENTIFIER("e".to_string()),
+            Token::EOF,
+        ];
+
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_single_ampersand() {
+        let error = Lexer::new("a & b").lex().unwrap_err();
+
+        let expected = HitungError::IllegalCharacter {
+            character: '&',
+            span: Span::new(2, 1, 1, 3),
+        };
+
+        assert_eq!(expected, error);
+    }
+
+    #[test]
+    fn test_function() {
+        let tokens = lex("fungsi luas(p, l) = p * l");
+
+        let expected = vec![
+            Token::Function,
+            Token::IDENTIFIER("luas".to_string()),
+            Token::LParen,
+            Token::IDENTIFIER("p".to_string()),
+            Token::Comma,
+            Token::IDENTIFIER("l".to_string()),
+            Token::RParen,
+            Token::ASSIGN,
+            Token::IDENTIFIER("p".to_string()),
+            Token::Mul,
+            Token::IDENTIFIER("l".to_string()),
+            Token::EOF,
+        ];
+
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_while() {
+        let tokens = lex("selama i < 3 lakukan i = i + 1");
+
+        let expected = vec![
+            Token::While,
+            Token::IDENTIFIER("i".to_string()),
+            Token::LT,
+            Token::from(3),
+            Token::Do,
+            Token::IDENTIFIER("i".to_string()),
+            Token::ASSIGN,
+            Token::IDENTIFIER("i".to_string()),
+            Token::Add,
+            Token::from(1),
+            Token::EOF,
+        ];
+
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_for() {
+        let tokens = lex("untuk i dari 1 sampai 9 langkah 2 lakukan i");
+
+        let expected = vec![
+            Token::For,
+            Token::IDENTIFIER("i".to_string()),
+            Token::From,
+            Token::from(1),
+            Token::To,
+            Token::from(9),
+            Token::Step,
+            Token::from(2),
+            Token::Do,
+            Token::IDENTIFIER("i".to_string()),
+            Token::EOF,
+        ];
+
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_dialect() {
+        let source = "jika a dan b maka 1 selain 0";
+        let indonesian = vec![
+            Token::If,
+            Token::IDENTIFIER("a".to_string()),
+            Token::And,
+            Token::IDENTIFIER("b".to_string()),
+            Token::Then,
+            Token::from(1),
+            Token::Else,
+            Token::from(0),
+            Token::EOF,
+        ];
+        assert_eq!(indonesian, lex(source));
+
+        let tokens: Vec<Token> = Lexer::with_dialect("if jika", Dialect::Indonesian)
+            .lex()
+            .unwrap()
+            .into_iter()
+            .map(|token| token.node)
+            .collect();
+        let expected = vec![Token::IDENTIFIER("if".to_string()), Token::If, Token::EOF];
+        assert_eq!(expected, tokens);
+
+        let tokens: Vec<Token> = Lexer::with_dialect("if jika", Dialect::English)
+            .lex()
+            .unwrap()
+            .into_iter()
+            .map(|token| token.node)
+            .collect();
+        let expected = vec![Token::If, Token::IDENTIFIER("jika".to_string()), Token::EOF];
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_comments() {
+        let tokens = lex("a = 1 # satu\n# dua\nb /* tiga /* empat */ */ * 2 /**/");
+
+        let expected = vec![
+            Token::IDENTIFIER("a".to_string()),
+            Token::ASSIGN,
+            Token::from(1),
+            Token::Newline,
+            Token::Newline,
+            Token::IDENTIFIER("b".to_string()),
+            Token::Mul,
+            Token::from(2),
+            Token::EOF,
+        ];
+
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_double_slash_is_not_a_comment() {
+        let tokens = lex("a = 1 // catatan");
+
+        let expected = vec![
+            Token::IDENTIFIER("a".to_string()),
+            Token::ASSIGN,
+            Token::from(1),
+            Token::FloorDiv,
+            Token::IDENTIFIER("catatan".to_string()),
+            Token::EOF,
+        ];
+
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_multiline_block_comment() {
+        let tokens = lex("a /* satu\ndua */ b");
+
+        let expected = vec![
+            Token::IDENTIFIER("a".to_string()),
+            Token::Newline,
+            Token::IDENTIFIER("b".to_string()),
+            Token::EOF,
+        ];
+
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_keep_comments() {
+        let tokens = Lexer::new("8 / 2 # bagi\n/* x */")
+            .keep_comments()
+            .lex()
+            .unwrap();
+
+        let expected = vec![
+            Spanned::new(Token::from(8), Span::new(0, 1, 1, 1)),
+            Spanned::new(Token::Div, Span::new(2, 1, 1, 3)),
+            Spanned::new(Token::from(2), Span::new(4, 1, 1, 5)),
+            Spanned::new(Token::Comment("# bagi".to_string()), Span::new(6, 6, 1, 7)),
+            Spanned::new(Token::Newline, Span::new(12, 1, 1, 13)),
+            Spanned::new(
+                Token::Comment("/* x */".to_string()),
+                Span::new(13, 7, 2, 1),
+            ),
+            Spanned::new(Token::EOF, Span::new(20, 0, 2, 8)),
+        ];
+
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_unterminated_comment() {
+        let error = Lexer::new("1 /* /* */ 2").lex().unwrap_err();
+
+        let expected = HitungError::UnterminatedComment {
+            span: Span::new(2, 2, 1, 3),
+        };
+
+        assert_eq!(expected, error);
+    }
+
+    #[test]
+    fn test_identifiers() {
+        let tokens = lex("x1 total_harga _nilai2 π jarak_ñ selain2");
+
+        let expected = vec![
+            Token::IDENTIFIER("x1".to_string()),
+            Token::IDENTIFIER("total_harga".to_string()),
+            Token::IDENTIFIER("_nilai2".to_string()),
+            Token::IDENTIFIER("π".to_string()),
+            Token::IDENTIFIER("jarak_ñ".to_string()),
+            Token::IDENTIFIER("selain2".to_string()),
+            Token::EOF,
+        ];
+
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_separators() {
+        let tokens = lex("a = 8\nb = a; b");
+
+        let expected = vec![
+            Token::IDENTIFIER("a".to_string()),
+            Token::ASSIGN,
+            Token::from(8),
+            Token::Newline,
+            Token::IDENTIFIER("b".to_string()),
+            Token::ASSIGN,
+            Token::IDENTIFIER("a".to_string()),
+            Token::Semicolon,
+            Token::IDENTIFIER("b".to_string()),
+            Token::EOF,
+        ];
+
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_span() {
+        let tokens = Lexer::new("a = 12\nb").lex().unwrap();
+
+        let expected = vec![
+            Spanned::new(Token::IDENTIFIER("a".to_string()), Span::new(0, 1, 1, 1)),
+            Spanned::new(Token::ASSIGN, Span::new(2, 1, 1, 3)),
+            Spanned::new(Token::from(12), Span::new(4, 2, 1, 5)),
+            Spanned::new(Token::Newline, Span::new(6, 1, 1, 7)),
+            Spanned::new(Token::IDENTIFIER("b".to_string()), Span::new(7, 1, 2, 1)),
+            Spanned::new(Token::EOF, Span::new(8, 0, 2, 2)),
+        ];
+
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_illegal_character() {
+        let error = Lexer::new("1 $ 2").lex().unwrap_err();
+
+        let expected = HitungError::IllegalCharacter {
+            character: '$',
+            span: Span::new(2, 1, 1, 3),
+        };
+
+        assert_eq!(expected, error);
+    }
+
+    #[test]
+    fn test_malformed_number() {
+        let error = Lexer::new("1.2.3").lex().unwrap_err();
+
+        assert_eq!(crate::error::ErrorKind::MalformedNumber, error.kind());
+    }
+
+    #[test]
+    fn test_number_literals() {
+        let tokens = lex("1e6 2.5E-3 1e+2 0xFF 0b1010 0o17 1_000_000 0xff_ff 1_234.567_8");
+
+        let expected = vec![
+            Token::Num(1e6),
+            Token::Num(2.5e-3),
+            Token::Num(100.0),
+            Token::Num(255.0),
+            Token::Num(10.0),
+            Token::Num(15.0),
+            Token::Num(1_000_000.0),
+            Token::Num(65535.0),
+            Token::Num(1_234.567_8),
+            Token::EOF,
+        ];
+
+        assert_eq!(expected, tokens);
+    }
+
+    #[test]
+    fn test_malformed_number_literals() {
+        for source in [
+            "1.2.3", "1e", "1e+", "0x", "0xG", "0b102", "1__0", "1_", "1_.5", "2x", "0x1.5",
+        ] {
+            let error = Lexer::new(source).lex().unwrap_err();
+
+            let expected = HitungError::MalformedNumber {
+                literal: source.to_string(),
+                span: Span::new(0, source.len(), 1, 1),
+            };
+            assert_eq!(expected, error, "{}", source);
+        }
+    }
+
+    #[test]
+    fn test_number_followed_by_operator() {
+        let tokens = lex("1e3-0x10+2");
+
+        let expected = vec![
+            Token::Num(1000.0),
+            Token::Sub,
+            Token::Num(16.0),
+            Token::Add,
+            Token::from(2),
+            Token::EOF,
+        ];
+
+        assert_eq!(expected, tokens);
+    }
 }
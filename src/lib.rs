@@ -0,0 +1,13 @@
+pub mod builtin;
+pub mod diagnostic;
+pub mod environment;
+pub mod error;
+pub mod expression;
+pub mod host;
+pub mod jit;
+pub mod keyword;
+pub mod lexer;
+pub mod message;
+pub mod parser;
+pub mod span;
+pub mod token;
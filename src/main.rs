@@ -1,31 +1,126 @@
+use std::fs;
 use std::io;
-use std::io::Write;
+use std::io::{IsTerminal, Read, Write};
+use std::process;
 
 use inkwell::context::Context;
 
-mod expression;
-mod jit;
-mod lexer;
-mod parser;
-mod token;
-
-use jit::Compiler;
+use tutorial_hitung::builtin::Constant;
+use tutorial_hitung::diagnostic::Diagnostic;
+use tutorial_hitung::jit::Compiler;
+use tutorial_hitung::keyword::Dialect;
+use tutorial_hitung::message::{self, CliError, CliMessage, Language};
 
 fn main() {
     let mut debug = false;
+    let mut echo = false;
+    let mut recursion_limit = None;
+    let mut dialect = Dialect::default();
+    let mut language = Language::from_env();
+    let mut expression = None;
+    let mut path = None;
 
-    for arg in std::env::args() {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
         match arg.as_str() {
             "debug" => debug = true,
-            _ => (),
+            "--echo" => echo = true,
+            "--keywords" => match args.next().map(|name| name.parse::<Dialect>()) {
+                Some(Ok(keywords)) => dialect = keywords,
+                Some(Err(err)) => usage_error(err, language),
+                None => usage_error(CliError::new(CliMessage::MissingKeywords), language),
+            },
+            "--lang" => match args.next().map(|name| name.parse::<Language>()) {
+                Some(Ok(lang)) => language = lang,
+                Some(Err(err)) => usage_error(err, language),
+                None => usage_error(CliError::new(CliMessage::MissingLanguage), language),
+            },
+            "--recursion-limit" => match args.next().map(|limit| limit.parse::<u64>()) {
+                Some(Ok(limit)) => recursion_limit = Some(limit),
+                _ => usage_error(CliError::new(CliMessage::MissingRecursionLimit), language),
+            },
+            "-e" => match args.next() {
+                Some(source) => expression = Some(source),
+                None => usage_error(CliError::new(CliMessage::MissingExpression), language),
+            },
+            "-h" | "--help" => {
+                println!("{}", message::cli_text(CliMessage::Usage, language));
+                return;
+            }
+            flag if flag.starts_with('-') => usage_error(
+                CliError::new(CliMessage::UnknownOption).with("option", flag),
+                language,
+            ),
+            _ if path.is_some() => usage_error(CliError::new(CliMessage::OneFile), language),
+            _ => path = Some(arg),
         }
     }
 
     let context = Context::create();
     let mut compiler = Compiler::new(&context, debug);
+    compiler.set_dialect(dialect);
+    if let Some(limit) = recursion_limit {
+        compiler.set_recursion_limit(limit);
+    }
+
+    if let Some(source) = expression {
+        run(&mut compiler, language, echo, "<expr>", &source);
+    } else if let Some(path) = path {
+        match fs::read_to_string(&path) {
+            Ok(source) => run(&mut compiler, language, echo, &path, &source),
+            Err(err) => {
+                let err = CliError::new(CliMessage::ReadFile)
+                    .with("path", &path)
+                    .with("reason", err);
+                fatal(err, language);
+            }
+        }
+    } else if !io::stdin().is_terminal() {
+        let mut source = String::new();
+        if let Err(err) = io::stdin().read_to_string(&mut source) {
+            fatal(
+                CliError::new(CliMessage::ReadStdin).with("reason", err),
+                language,
+            );
+        }
+        run(&mut compiler, language, echo, "<stdin>", &source);
+    } else {
+        repl(&mut compiler, language);
+    }
+}
+
+fn usage_error(err: CliError, language: Language) -> ! {
+    let (error, _) = message::heading(language);
+    eprintln!(
+        "{}: {}\n\n{}",
+        error,
+        err.render(language),
+        message::cli_text(CliMessage::Usage, language)
+    );
+    process::exit(2);
+}
 
+fn fatal(err: CliError, language: Language) -> ! {
+    let (error, _) = message::heading(language);
+    eprintln!("{}: {}", error, err.render(language));
+    process::exit(1);
+}
+
+/// Run a whole program, exiting with a nonzero status when it fails. Prints
+/// the value of the last statement, or of every one with `echo`.
+fn run(compiler: &mut Compiler, language: Language, echo: bool, name: &str, source: &str) {
+    match compiler.compile_statements(source) {
+        Ok(values) => print_values(&values, echo),
+        Err(err) => {
+            let diagnostic = Diagnostic::from_error(&err, language, compiler.dialect());
+            eprint!("{}", diagnostic.render(name, source));
+            process::exit(1);
+        }
+    }
+}
+
+fn repl(compiler: &mut Compiler, language: Language) {
     loop {
-        // repl
         println!();
         print!("> ");
 
@@ -33,16 +128,45 @@ fn main() {
 
         let mut input = String::new();
 
-        io::stdin()
-            .read_line(&mut input)
-            .expect("Could not read from standard input.");
+        match io::stdin().read_line(&mut input) {
+            // Ctrl-D
+            Ok(0) => {
+                println!();
+                break;
+            }
+            Ok(_) => (),
+            Err(err) => {
+                let (error, _) = message::heading(language);
+                let err = CliError::new(CliMessage::ReadStdin).with("reason", err);
+                eprintln!("{}: {}", error, err.render(language));
+                break;
+            }
+        }
+
+        if let ":konstanta" | ":constants" = input.trim() {
+            for constant in Constant::all() {
+                println!("{} = {}", constant.name, constant.value);
+            }
+            continue;
+        }
 
-        match compiler.compile_source(input.as_str()) {
-            Ok(result) => println!("{}", result),
+        match compiler.compile_statements(input.as_str()) {
+            Ok(values) => print_values(&values, true),
             Err(err) => {
-                eprintln!("Error {:?}", err);
-                break
-            },
+                let diagnostic = Diagnostic::from_error(&err, language, compiler.dialect());
+                eprint!("{}", diagnostic.render("<repl>", &input));
+            }
         }
     }
 }
+
+fn print_values(values: &[f64], echo: bool) {
+    let skip = if echo {
+        0
+    } else {
+        values.len().saturating_sub(1)
+    };
+    for value in &values[skip..] {
+        println!("{}", value);
+    }
+}
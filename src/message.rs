@@ -0,0 +1,417 @@
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::ErrorKind;
+use crate::token::Token;
+
+/// Language of the error messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Language {
+    #[default]
+    English,
+    Indonesian,
+}
+
+impl Language {
+    /// Language of a locale like `id_ID.UTF-8`, English unless it is
+    /// Indonesian.
+    pub fn from_locale(locale: &str) -> Self {
+        if locale.starts_with("id") {
+            Language::Indonesian
+        } else {
+            Language::English
+        }
+    }
+
+    /// Language picked by the `LANG` environment variable.
+    pub fn from_env() -> Self {
+        env::var("LANG")
+            .map(|locale| Language::from_locale(&locale))
+            .unwrap_or_default()
+    }
+}
+
+impl FromStr for Language {
+    type Err = CliError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "id" => Ok(Language::Indonesian),
+            "en" => Ok(Language::English),
+            _ => Err(CliError::new(CliMessage::UnknownLanguage).with("name", name)),
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Language::English => write!(f, "en"),
+            Language::Indonesian => write!(f, "id"),
+        }
+    }
+}
+
+/// Templates shown for one kind of error. Placeholders like `{name}` are
+/// filled in with [`fill`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Text {
+    pub message: &'static str,
+    pub label: Option<&'static str>,
+    pub help: Option<&'static str>,
+}
+
+impl Text {
+    const fn new(message: &'static str) -> Self {
+        Text {
+            message,
+            label: None,
+            help: None,
+        }
+    }
+
+    const fn label(mut self, label: &'static str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    const fn help(mut self, help: &'static str) -> Self {
+        self.help = Some(help);
+        self
+    }
+}
+
+/// The message catalog.
+pub fn text(kind: ErrorKind, language: Language) -> Text {
+    match language {
+        Language::English => english(kind),
+        Language::Indonesian => indonesian(kind),
+    }
+}
+
+fn english(kind: ErrorKind) -> Text {
+    match kind {
+        ErrorKind::IllegalCharacter => {
+            Text::new("illegal character `{character}`").label("not allowed here")
+        }
+        ErrorKind::UnterminatedComment => Text::new("unterminated block comment")
+            .label("this comment is never closed")
+            .help("close it with `*/`"),
+        ErrorKind::MalformedNumber => Text::new("malformed number `{literal}`")
+            .label("not a valid number")
+            .help("numbers look like `42`, `1.5`, `1e6`, `0xFF`, `0b1010` or `1_000_000`"),
+        ErrorKind::UnexpectedToken => {
+            Text::new("unexpected token `{token}`").label("unexpected here")
+        }
+        ErrorKind::UnexpectedEof => {
+            Text::new("unexpected end of input").label("expected more input")
+        }
+        ErrorKind::UnmatchedParen => Text::new("unmatched parenthesis")
+            .label("this parenthesis is never matched")
+            .help("every `(` needs a matching `)`"),
+        ErrorKind::ExpectedToken => {
+            Text::new("expected `{expected}`, found `{found}`").label("expected `{expected}` here")
+        }
+        ErrorKind::ReservedKeyword => Text::new("`{keyword}` is a reserved keyword")
+            .label("cannot be used as a name")
+            .help("keywords cannot name a variable, function or parameter, pick another name"),
+        ErrorKind::UndeclaredVariable => Text::new("variable `{name}` is not declared")
+            .label("not declared")
+            .help("assign a value first, e.g. `{name} = 0`"),
+        ErrorKind::UndeclaredFunction => Text::new("function `{name}` is not defined")
+            .label("not defined")
+            .help("define it first, e.g. `{function} {name}(x) = x`"),
+        ErrorKind::ArityMismatch => {
+            Text::new("function `{name}` takes {expected} argument(s) but {found} were given")
+                .label("expected {expected} argument(s)")
+        }
+        ErrorKind::DuplicateParameter => Text::new("parameter `{name}` is declared more than once")
+            .label("every parameter needs its own name"),
+        ErrorKind::DuplicateFunction => Text::new("function `{name}` is defined more than once")
+            .label("defined again here")
+            .help("give every function in one input its own name"),
+        ErrorKind::InvalidAssignment => Text::new("assignment must be to a variable")
+            .label("cannot assign to this")
+            .help("the left side of `=` must be a variable name"),
+        ErrorKind::ConstantAssignment => Text::new("cannot assign to constant `{name}`")
+            .label("constant")
+            .help("constants are read-only, store the value under another name"),
+        ErrorKind::UnsupportedOperator => {
+            Text::new("operator `{operator}` is not supported here").label("not supported")
+        }
+        ErrorKind::ZeroStep => Text::new("the step of a counted loop is zero")
+            .label("this loop would never end")
+            .help("count up with a positive `{step}` or down with a negative one"),
+        ErrorKind::RecursionLimit => Text::new("recursion went deeper than {limit} calls")
+            .label("while evaluating this")
+            .help("make sure every recursive function reaches a case without a call"),
+        ErrorKind::Llvm => Text::new("LLVM error: {message}"),
+    }
+}
+
+fn indonesian(kind: ErrorKind) -> Text {
+    match kind {
+        ErrorKind::IllegalCharacter => {
+            Text::new("karakter `{character}` tidak dikenal").label("tidak boleh di sini")
+        }
+        ErrorKind::UnterminatedComment => Text::new("komentar blok tidak ditutup")
+            .label("komentar ini tidak pernah ditutup")
+            .help("tutup dengan `*/`"),
+        ErrorKind::MalformedNumber => Text::new("angka `{literal}` tidak valid")
+            .label("bukan angka yang valid")
+            .help("angka ditulis seperti `42`, `1.5`, `1e6`, `0xFF`, `0b1010` atau `1_000_000`"),
+        ErrorKind::UnexpectedToken => {
+            Text::new("token `{token}` tidak diharapkan").label("tidak diharapkan di sini")
+        }
+        ErrorKind::UnexpectedEof => {
+            Text::new("masukan berakhir terlalu cepat").label("masukan masih kurang")
+        }
+        ErrorKind::UnmatchedParen => Text::new("tanda kurung tidak berpasangan")
+            .label("tanda kurung ini tidak punya pasangan")
+            .help("setiap `(` harus ditutup dengan `)`"),
+        ErrorKind::ExpectedToken => Text::new("seharusnya `{expected}`, bukan `{found}`")
+            .label("seharusnya `{expected}` di sini"),
+        ErrorKind::ReservedKeyword => Text::new("`{keyword}` adalah kata kunci")
+            .label("tidak bisa dipakai sebagai nama")
+            .help("kata kunci tidak bisa menjadi nama variabel, fungsi atau parameter, pilih nama lain"),
+        ErrorKind::UndeclaredVariable => Text::new("variabel `{name}` belum dideklarasikan")
+            .label("belum dideklarasikan")
+            .help("beri nilai terlebih dahulu, misalnya `{name} = 0`"),
+        ErrorKind::UndeclaredFunction => Text::new("fungsi `{name}` belum didefinisikan")
+            .label("belum didefinisikan")
+            .help("definisikan terlebih dahulu, misalnya `{function} {name}(x) = x`"),
+        ErrorKind::ArityMismatch => {
+            Text::new("fungsi `{name}` menerima {expected} argumen tetapi diberi {found}")
+                .label("seharusnya {expected} argumen")
+        }
+        ErrorKind::DuplicateParameter => {
+            Text::new("parameter `{name}` dideklarasikan lebih dari sekali")
+                .label("setiap parameter harus punya nama sendiri")
+        }
+        ErrorKind::DuplicateFunction => {
+            Text::new("fungsi `{name}` didefinisikan lebih dari sekali")
+                .label("didefinisikan lagi di sini")
+                .help("beri setiap fungsi dalam satu masukan nama sendiri")
+        }
+        ErrorKind::InvalidAssignment => Text::new("penugasan harus ke sebuah variabel")
+            .label("tidak bisa diberi nilai")
+            .help("sisi kiri `=` harus berupa nama variabel"),
+        ErrorKind::ConstantAssignment => Text::new("konstanta `{name}` tidak bisa diberi nilai")
+            .label("konstanta")
+            .help("konstanta hanya bisa dibaca, simpan nilainya dengan nama lain"),
+        ErrorKind::UnsupportedOperator => {
+            Text::new("operator `{operator}` tidak didukung di sini").label("tidak didukung")
+        }
+        ErrorKind::ZeroStep => Text::new("langkah perulangan terhitung bernilai nol")
+            .label("perulangan ini tidak akan pernah berhenti")
+            .help("hitung naik dengan `{step}` positif atau turun dengan yang negatif"),
+        ErrorKind::RecursionLimit => Text::new("rekursi lebih dalam dari {limit} panggilan")
+            .label("saat mengevaluasi ini")
+            .help("pastikan setiap fungsi rekursif punya kasus tanpa pemanggilan"),
+        ErrorKind::Llvm => Text::new("galat LLVM: {message}"),
+    }
+}
+
+/// Messages of the command line around the compiler, not about the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CliMessage {
+    Usage,
+    ReadFile,
+    ReadStdin,
+    UnknownOption,
+    OneFile,
+    MissingExpression,
+    MissingKeywords,
+    MissingLanguage,
+    MissingRecursionLimit,
+    UnknownKeywords,
+    UnknownLanguage,
+}
+
+/// A command line error, rendered in the language of the session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError {
+    pub message: CliMessage,
+    pub arguments: Vec<(&'static str, String)>,
+}
+
+impl CliError {
+    pub fn new(message: CliMessage) -> Self {
+        CliError {
+            message,
+            arguments: Vec::new(),
+        }
+    }
+
+    pub fn with(mut self, key: &'static str, value: impl fmt::Display) -> Self {
+        self.arguments.push((key, value.to_string()));
+        self
+    }
+
+    pub fn render(&self, language: Language) -> String {
+        fill(cli_text(self.message, language), &self.arguments)
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(Language::default()))
+    }
+}
+
+/// The message catalog of the command line.
+pub fn cli_text(message: CliMessage, language: Language) -> &'static str {
+    match language {
+        Language::English => match message {
+            CliMessage::Usage => USAGE_EN,
+            CliMessage::ReadFile => "could not read `{path}`: {reason}",
+            CliMessage::ReadStdin => "could not read from standard input: {reason}",
+            CliMessage::UnknownOption => "unknown option `{option}`",
+            CliMessage::OneFile => "only one file can be run at a time",
+            CliMessage::MissingExpression => "`-e` needs an expression",
+            CliMessage::MissingKeywords => "`--keywords` needs `id`, `en` or `both`",
+            CliMessage::MissingLanguage => "`--lang` needs `id` or `en`",
+            CliMessage::MissingRecursionLimit => "`--recursion-limit` needs a whole number",
+            CliMessage::UnknownKeywords => {
+                "unknown keyword language `{name}`, expected `id`, `en` or `both`"
+            }
+            CliMessage::UnknownLanguage => "unknown language `{name}`, expected `id` or `en`",
+        },
+        Language::Indonesian => match message {
+            CliMessage::Usage => USAGE_ID,
+            CliMessage::ReadFile => "tidak bisa membaca `{path}`: {reason}",
+            CliMessage::ReadStdin => "tidak bisa membaca masukan standar: {reason}",
+            CliMessage::UnknownOption => "opsi `{option}` tidak dikenal",
+            CliMessage::OneFile => "hanya satu berkas yang bisa dijalankan sekaligus",
+            CliMessage::MissingExpression => "`-e` membutuhkan sebuah ekspresi",
+            CliMessage::MissingKeywords => "`--keywords` membutuhkan `id`, `en` atau `both`",
+            CliMessage::MissingLanguage => "`--lang` membutuhkan `id` atau `en`",
+            CliMessage::MissingRecursionLimit => "`--recursion-limit` membutuhkan bilangan bulat",
+            CliMessage::UnknownKeywords => {
+                "bahasa kata kunci `{name}` tidak dikenal, seharusnya `id`, `en` atau `both`"
+            }
+            CliMessage::UnknownLanguage => {
+                "bahasa `{name}` tidak dikenal, seharusnya `id` atau `en`"
+            }
+        },
+    }
+}
+
+const USAGE_EN: &str = "\
+usage: hitung [OPTIONS] [FILE | -e EXPRESSION]
+
+Without FILE or -e the program is read from standard input when it is piped,
+otherwise the interactive REPL is started. In the REPL `:konstanta` (or
+`:constants`) lists the builtin constants.
+
+options:
+    -e EXPRESSION   evaluate EXPRESSION and print the result
+    --echo          print the value of every statement, not only the last one
+    --keywords LANG keywords to accept: `id` (jika, maka, selain),
+                    `en` (if, then, else) or `both` (default)
+    --lang LANG     language of the messages, `id` or `en`, taken from the
+                    `LANG` environment variable when not given
+    --recursion-limit N
+                    stop when function calls nest deeper than N (default 10000)
+    debug           print the AST and the LLVM IR
+    -h, --help      show this message";
+
+const USAGE_ID: &str = "\
+pemakaian: hitung [OPSI] [BERKAS | -e EKSPRESI]
+
+Tanpa BERKAS atau -e program dibaca dari masukan standar bila disalurkan lewat
+pipa, selain itu REPL interaktif dijalankan. Di REPL `:konstanta` (atau
+`:constants`) menampilkan konstanta bawaan.
+
+opsi:
+    -e EKSPRESI     evaluasi EKSPRESI dan cetak hasilnya
+    --echo          cetak nilai setiap pernyataan, bukan hanya yang terakhir
+    --keywords BHS  kata kunci yang diterima: `id` (jika, maka, selain),
+                    `en` (if, then, else) atau `both` (bawaan)
+    --lang BHS      bahasa pesan, `id` atau `en`, diambil dari variabel
+                    lingkungan `LANG` bila tidak diberikan
+    --recursion-limit N
+                    berhenti bila pemanggilan fungsi bersarang lebih dari N
+                    (bawaan 10000)
+    debug           cetak AST dan LLVM IR
+    -h, --help      tampilkan pesan ini";
+
+/// How the construct expecting `expected` is written, keywords are
+/// placeholders like `{then}`.
+pub fn syntax_help(expected: &Token, language: Language) -> Option<&'static str> {
+    let help = match (expected, language) {
+        (Token::Then | Token::Else, Language::English) => {
+            "a conditional looks like `{if} condition {then} value {else} value`"
+        }
+        (Token::Then | Token::Else, Language::Indonesian) => {
+            "percabangan ditulis `{if} kondisi {then} nilai {else} nilai`"
+        }
+        (Token::Do, Language::English) => "a loop looks like `{while} condition {do} body`",
+        (Token::Do, Language::Indonesian) => "perulangan ditulis `{while} kondisi {do} badan`",
+        (Token::From | Token::To, Language::English) => {
+            "a counted loop looks like `{for} i {from} 1 {to} 10 {do} body`"
+        }
+        (Token::From | Token::To, Language::Indonesian) => {
+            "perulangan terhitung ditulis `{for} i {from} 1 {to} 10 {do} badan`"
+        }
+        _ => return None,
+    };
+
+    Some(help)
+}
+
+/// Words around a rendered diagnostic, `error` and `help`.
+pub fn heading(language: Language) -> (&'static str, &'static str) {
+    match language {
+        Language::English => ("error", "help"),
+        Language::Indonesian => ("galat", "bantuan"),
+    }
+}
+
+/// Replace every `{key}` in `template` with its value.
+pub fn fill(template: &str, arguments: &[(&str, String)]) -> String {
+    arguments
+        .iter()
+        .fold(template.to_string(), |text, (key, value)| {
+            text.replace(&format!("{{{}}}", key), value)
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_locale() {
+        assert_eq!(Language::Indonesian, Language::from_locale("id_ID.UTF-8"));
+        assert_eq!(Language::English, Language::from_locale("en_US.UTF-8"));
+        assert_eq!(Language::English, Language::from_locale("C"));
+    }
+
+    #[test]
+    fn test_cli_error() {
+        let error = "jawa".parse::<Language>().unwrap_err();
+
+        assert_eq!(
+            "bahasa `jawa` tidak dikenal, seharusnya `id` atau `en`",
+            error.render(Language::Indonesian)
+        );
+        assert_eq!(
+            "unknown language `jawa`, expected `id` or `en`",
+            error.to_string()
+        );
+    }
+
+    #[test]
+    fn test_fill() {
+        let arguments = [("name", "b".to_string())];
+
+        let actual = fill(
+            text(ErrorKind::UndeclaredVariable, Language::Indonesian).message,
+            &arguments,
+        );
+
+        assert_eq!("variabel `b` belum dideklarasikan", actual);
+    }
+}
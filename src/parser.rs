@@ -2,52 +2,116 @@ use std::iter::Iterator;
 use std::iter::Peekable;
 use std::vec::IntoIter;
 
-use crate::expression::Expression;
+use crate::error::{HitungError, Result};
+use crate::expression::{Expression, Function, Program};
+use crate::span::{Span, Spanned};
 use crate::token::Token;
 
-pub struct Parser {
-    tokens: Peekable<IntoIter<Token>>,
+// Binding power of prefix `+` and `-`, tighter than `*` and `/`.
+const PREFIX_BP: usize = 25;
+// Binding power of prefix `not`, tighter than `and` but looser than `<`.
+const NOT_BP: usize = 4;
+
+pub struct Parser<'a> {
+    tokens: Peekable<IntoIter<Spanned<Token>>>,
+    end: Span,
+    // the tokens were lexed from, for quoting them in errors
+    source: &'a str,
 }
 
-impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
+impl<'a> Parser<'a> {
+    pub fn new(tokens: Vec<Spanned<Token>>) -> Self {
+        let end = tokens.last().map(|token| token.span).unwrap_or_default();
+
         Parser {
             tokens: tokens.into_iter().peekable(),
+            end,
+            source: "",
         }
     }
 
-    pub fn handle_next(&mut self) -> Result<Token, String> {
-        self.tokens.next().ok_or("Error get next token".to_string())
+    /// Quote tokens in errors the way they are written in `source`.
+    pub fn with_source(mut self, source: &'a str) -> Self {
+        self.source = source;
+        self
+    }
+
+    /// Parser for the tokens inside a pair of parentheses, running out of
+    /// input at the closing one spanning `close`.
+    fn group(&self, tokens: Vec<Spanned<Token>>, close: Span) -> Self {
+        Parser {
+            tokens: tokens.into_iter().peekable(),
+            end: close,
+            source: self.source,
+        }
+    }
+
+    /// `token` as it is written in the source, e.g. `maka` rather than
+    /// `then`.
+    fn spelling(&self, token: &Spanned<Token>) -> String {
+        self.source
+            .get(token.span.offset..token.span.end())
+            .filter(|text| !text.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| token.node.to_string())
+    }
+
+    pub fn handle_next(&mut self) -> Result<Spanned<Token>> {
+        let end = self.end;
+        self.tokens
+            .next()
+            .ok_or(HitungError::UnexpectedEof { span: end })
     }
 
     //Null Denotation
-    pub fn nud(&mut self, token: Token) -> Result<Expression, String> {
-        match token {
-            Token::ILLEGAL => Err("Input not supported".to_string()),
-            Token::IDENTIFIER(i) => Ok(Expression::Variable(i)),
-            Token::Num(n) => Ok(Expression::Num(n)),
+    pub fn nud(&mut self, token: Spanned<Token>) -> Result<Spanned<Expression>> {
+        let span = token.span;
+
+        // `dari = 1`, a keyword used as a variable
+        if token.node.is_keyword() && self.peek_is(&Token::ASSIGN) {
+            return Err(HitungError::ReservedKeyword {
+                keyword: self.spelling(&token),
+                span,
+            });
+        }
+
+        match token.node {
+            Token::IDENTIFIER(i) => Ok(Spanned::new(Expression::Variable(i), span)),
+            Token::Num(n) => Ok(Spanned::new(Expression::Num(n), span)),
             Token::Sub | Token::Add => {
-                let tok = self.handle_next()?;
-                match tok {
-                    Token::Num(n) => Ok(Expression::Unary(token, Box::new(Expression::Num(n)))),
-                    _ => Err("Input not supported".to_string()),
-                }
+                let operand = self.expr(PREFIX_BP)?;
+                let span = span.to(operand.span);
+                Ok(Spanned::new(
+                    Expression::Unary(token.node, Box::new(operand)),
+                    span,
+                ))
+            }
+            Token::Not => {
+                let operand = self.expr(NOT_BP)?;
+                let span = span.to(operand.span);
+                Ok(Spanned::new(
+                    Expression::Unary(token.node, Box::new(operand)),
+                    span,
+                ))
             }
             Token::LParen => {
                 let mut parenthesis = Vec::new();
                 let mut counter: usize = 1;
 
                 while let Some(token) = self.tokens.next() {
-                    match &token {
+                    match &token.node {
+                        // a parenthesised expression may span several lines
+                        Token::Newline => continue,
                         Token::LParen => counter += 1,
                         Token::RParen => {
                             match counter {
                                 1 => {
-                                    return Parser::new(parenthesis)
-                                        .expr(0)
-                                        .map(|t| Expression::Paren(Box::new(t)));
+                                    let close = token.span;
+                                    return self.group(parenthesis, close).group_expr().map(|t| {
+                                        Spanned::new(Expression::Paren(Box::new(t)), span.to(close))
+                                    });
                                 }
-                                0 => return Err("Unmatched closing paren".to_string()),
+                                0 => return Err(HitungError::UnmatchedParen { span: token.span }),
                                 _ => {}
                             };
                             counter -= 1;
@@ -57,63 +121,317 @@ impl Parser {
                     parenthesis.push(token);
                 }
 
-                Err("Unmatched closing paren".to_string())
+                Err(HitungError::UnmatchedParen { span })
             }
-            Token::RParen => Err("Unmatched closing paren".to_string()),
+            Token::RParen => Err(HitungError::UnmatchedParen { span }),
             Token::If => {
-                let lhs = self.handle_next()?;
-                let cmp = self.handle_next()?;
-                let rhs = self.handle_next()?;
+                let condition = self.expr(0)?;
+
+                self.expect(Token::Then)?;
+                let then_expression = self.expr(0)?;
+
+                self.expect(Token::Else)?;
+                let else_expression = self.expr(0)?;
+
+                let span = span.to(else_expression.span);
+                Ok(Spanned::new(
+                    Expression::Conditional(
+                        Box::new(condition),
+                        Box::new(then_expression),
+                        Box::new(else_expression),
+                    ),
+                    span,
+                ))
+            }
+            Token::While => {
+                let condition = self.expr(0)?;
+
+                self.expect(Token::Do)?;
+                let body = self.expr(0)?;
+
+                let span = span.to(body.span);
+                Ok(Spanned::new(
+                    Expression::While(Box::new(condition), Box::new(body)),
+                    span,
+                ))
+            }
+            Token::For => {
+                let name = self.identifier()?;
 
-                let lhs = self.nud(lhs)?;
-                let rhs = self.nud(rhs)?;
+                self.expect(Token::From)?;
+                let start = self.expr(0)?;
 
-                let left = Box::new(Expression::Binary(Box::new(lhs), cmp, Box::new(rhs)));
+                self.expect(Token::To)?;
+                let end = self.expr(0)?;
 
-                let _then = self.handle_next()?;
-                let then_branch = self.handle_next()?;
-                let then_expression = self.nud(then_branch)?;
+                self.skip_newlines();
+                let step = if self.peek_is(&Token::Step) {
+                    self.handle_next()?;
+                    Some(Box::new(self.expr(0)?))
+                } else {
+                    None
+                };
 
-                let _else_if = self.handle_next()?;
-                let else_branch = self.handle_next()?;
-                let else_expression = self.nud(else_branch)?;
+                self.expect(Token::Do)?;
+                let body = self.expr(0)?;
 
-                Ok(Expression::Conditional(
-                    left,
-                    Box::new(then_expression),
-                    Box::new(else_expression),
+                let span = span.to(body.span);
+                Ok(Spanned::new(
+                    Expression::For(
+                        name.node,
+                        Box::new(start),
+                        Box::new(end),
+                        step,
+                        Box::new(body),
+                    ),
+                    span,
                 ))
             }
-            _ => Err(format!("Token {:?} error", token)),
+            Token::EOF => Err(HitungError::UnexpectedEof { span }),
+            token => Err(HitungError::UnexpectedToken { token, span }),
         }
     }
 
     //Left Denotation
-    pub fn led(&mut self, bp: usize, left: Expression, token: Token) -> Result<Expression, String> {
-        match token {
-            Token::Add | Token::Sub | Token::Mul | Token::Div | Token::ASSIGN => {
-                let rhs = self.expr(bp)?;
-                Ok(Expression::Binary(Box::new(left), token, Box::new(rhs)))
+    pub fn led(
+        &mut self,
+        bp: usize,
+        left: Spanned<Expression>,
+        token: Spanned<Token>,
+    ) -> Result<Spanned<Expression>> {
+        match token.node {
+            Token::Add
+            | Token::Sub
+            | Token::Mul
+            | Token::Div
+            | Token::FloorDiv
+            | Token::Mod
+            | Token::Pow
+            | Token::ASSIGN
+            | Token::EQ
+            | Token::NEQ
+            | Token::LT
+            | Token::LTE
+            | Token::GT
+            | Token::GTE
+            | Token::And
+            | Token::Or => {
+                // assignment and power are right associative: `a = b = 1` is
+                // `a = (b = 1)` and `2 ^ 3 ^ 2` is `2 ^ (3 ^ 2)`
+                let rbp = if token.node == Token::ASSIGN || token.node == Token::Pow {
+                    bp - 1
+                } else {
+                    bp
+                };
+                let rhs = self.expr(rbp)?;
+                let span = left.span.to(rhs.span);
+                Ok(Spanned::new(
+                    Expression::Binary(Box::new(left), token.node, Box::new(rhs)),
+                    span,
+                ))
+            }
+            Token::LParen => match left.node {
+                Expression::Variable(name) => {
+                    let args = self.arguments()?;
+                    let close = self.expect(Token::RParen).map_err(|err| match err {
+                        HitungError::ExpectedToken {
+                            found: Token::EOF, ..
+                        } => HitungError::UnmatchedParen { span: token.span },
+                        err => err,
+                    })?;
+
+                    Ok(Spanned::new(
+                        Expression::Call(name, args),
+                        left.span.to(close),
+                    ))
+                }
+                _ => Err(HitungError::UnexpectedToken {
+                    token: token.node,
+                    span: token.span,
+                }),
+            },
+            _ => Err(HitungError::UnexpectedToken {
+                token: token.node,
+                span: token.span,
+            }),
+        }
+    }
+
+    /// Comma separated arguments of a call, up to but without the `)`.
+    fn arguments(&mut self) -> Result<Vec<Spanned<Expression>>> {
+        let mut args = Vec::new();
+
+        self.skip_newlines();
+        if self.peek_is(&Token::RParen) {
+            return Ok(args);
+        }
+
+        loop {
+            args.push(self.expr(0)?);
+            self.skip_newlines();
+
+            if !self.peek_is(&Token::Comma) {
+                return Ok(args);
+            }
+            self.handle_next()?;
+            self.skip_newlines();
+        }
+    }
+
+    /// `fungsi name(a, b) = body`, starting at the `fungsi` keyword.
+    fn function(&mut self) -> Result<Spanned<Function>> {
+        let keyword = self.handle_next()?;
+        let name = self.identifier()?;
+
+        self.expect(Token::LParen)?;
+        let mut params = Vec::new();
+        if !self.peek_is(&Token::RParen) {
+            loop {
+                params.push(self.identifier()?);
+
+                if !self.peek_is(&Token::Comma) {
+                    break;
+                }
+                self.handle_next()?;
+                self.skip_newlines();
+            }
+        }
+        self.expect(Token::RParen)?;
+        self.expect(Token::ASSIGN)?;
+
+        let body = self.expr(0)?;
+        let span = keyword.span.to(body.span);
+
+        Ok(Spanned::new(Function { name, params, body }, span))
+    }
+
+    fn identifier(&mut self) -> Result<Spanned<String>> {
+        let token = self.handle_next()?;
+
+        match token.node {
+            Token::IDENTIFIER(name) => Ok(Spanned::new(name, token.span)),
+            ref keyword if keyword.is_keyword() => Err(HitungError::ReservedKeyword {
+                keyword: self.spelling(&token),
+                span: token.span,
+            }),
+            Token::EOF => Err(HitungError::UnexpectedEof { span: token.span }),
+            node => Err(HitungError::UnexpectedToken {
+                token: node,
+                span: token.span,
+            }),
+        }
+    }
+
+    /// Consume the `expected` token that has to come next and return its
+    /// span. Newlines around it are skipped so a conditional can be spread
+    /// over several lines.
+    fn expect(&mut self, expected: Token) -> Result<Span> {
+        self.skip_newlines();
+
+        let token = self.handle_next()?;
+        if token.node != expected {
+            return Err(HitungError::ExpectedToken {
+                expected,
+                found: token.node,
+                span: token.span,
+            });
+        }
+
+        self.skip_newlines();
+        Ok(token.span)
+    }
+
+    fn peek_is(&mut self, token: &Token) -> bool {
+        self.tokens
+            .peek()
+            .is_some_and(|peeked| &peeked.node == token)
+    }
+
+    fn skip_newlines(&mut self) {
+        while let Some(token) = self.tokens.peek() {
+            if token.node != Token::Newline {
+                break;
+            }
+            self.tokens.next();
+        }
+    }
+
+    /// Parse statements separated by newlines or `;` up to the end of input.
+    pub fn program(&mut self) -> Result<Program> {
+        let mut functions = Vec::new();
+        let mut statements = Vec::new();
+
+        loop {
+            self.skip_separators();
+
+            match self.tokens.peek() {
+                None => break,
+                Some(token) if token.node == Token::EOF => break,
+                _ => {}
+            }
+
+            if self.peek_is(&Token::Function) {
+                functions.push(self.function()?);
+            } else {
+                statements.push(self.expr(0)?);
+            }
+
+            match self.tokens.peek() {
+                None => {}
+                Some(token) if token.node.is_separator() || token.node == Token::EOF => {}
+                Some(_) => {
+                    let token = self.handle_next()?;
+                    return Err(match token.node {
+                        Token::RParen => HitungError::UnmatchedParen { span: token.span },
+                        node => HitungError::UnexpectedToken {
+                            token: node,
+                            span: token.span,
+                        },
+                    });
+                }
+            }
+        }
+
+        Ok(Program {
+            functions,
+            statements,
+        })
+    }
+
+    fn skip_separators(&mut self) {
+        while let Some(token) = self.tokens.peek() {
+            if !token.node.is_separator() {
+                break;
             }
-            _ => Err(format!("Token {:?} error", token)),
+            self.tokens.next();
         }
     }
 
-    pub fn expr(&mut self, rbp: usize) -> Result<Expression, String> {
+    /// The one expression between a pair of parentheses, every token has to
+    /// belong to it.
+    fn group_expr(&mut self) -> Result<Spanned<Expression>> {
+        let expression = self.expr(0)?;
+
+        match self.tokens.next() {
+            None => Ok(expression),
+            Some(token) => Err(HitungError::UnexpectedToken {
+                token: token.node,
+                span: token.span,
+            }),
+        }
+    }
+
+    pub fn expr(&mut self, rbp: usize) -> Result<Spanned<Expression>> {
         let first_token = self.handle_next()?;
         let mut left = self.nud(first_token)?;
 
         while let Some(peeked) = self.tokens.peek() {
-            if *peeked == Token::ILLEGAL {
-                return Err("Input not supported".to_string());
-            }
-
-            if rbp >= peeked.lbp() {
+            if rbp >= peeked.node.lbp() {
                 break;
             }
 
             let op = self.handle_next()?;
-            left = self.led(op.lbp(), left, op)?;
+            left = self.led(op.node.lbp(), left, op)?;
         }
 
         Ok(left)
@@ -124,6 +442,14 @@ impl Parser {
 mod test {
     use super::*;
 
+    fn spanned(tokens: Vec<Token>) -> Vec<Spanned<Token>> {
+        tokens.into_iter().map(Spanned::from).collect()
+    }
+
+    fn node(expression: Expression) -> Box<Spanned<Expression>> {
+        Box::new(Spanned::from(expression))
+    }
+
     #[test]
     fn test_nud() {
         let tokens = vec![
@@ -133,12 +459,12 @@ mod test {
             Token::from(2),
             Token::EOF,
         ];
-        let expression = Parser::new(tokens).expr(0).unwrap();
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
 
         let expected = Expression::Binary(
-            Box::new(Expression::Unary(Token::Sub, Box::new(Expression::from(3)))),
+            node(Expression::Unary(Token::Sub, node(Expression::from(3)))),
             Token::Mul,
-            Box::new(Expression::from(2)),
+            node(Expression::from(2)),
         );
 
         assert_eq!(expected, expression);
@@ -147,9 +473,12 @@ mod test {
     #[test]
     fn test_error() {
         let tokens = vec![Token::Mul, Token::from(2), Token::EOF];
-        let expression = Parser::new(tokens).expr(0).map_err(|e| e);
+        let expression = Parser::new(spanned(tokens)).expr(0);
 
-        let expected = Err(String::from("Token Mul error"));
+        let expected = Err(HitungError::UnexpectedToken {
+            token: Token::Mul,
+            span: Span::default(),
+        });
 
         assert_eq!(expected, expression);
     }
@@ -157,12 +486,12 @@ mod test {
     #[test]
     fn test_binary() {
         let tokens = vec![Token::from(3), Token::Div, Token::from(2), Token::EOF];
-        let expression = Parser::new(tokens).expr(0).unwrap();
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
 
         let expected = Expression::Binary(
-            Box::new(Expression::from(3)),
+            node(Expression::from(3)),
             Token::Div,
-            Box::new(Expression::from(2)),
+            node(Expression::from(2)),
         );
 
         assert_eq!(expected, expression);
@@ -171,9 +500,9 @@ mod test {
     #[test]
     fn test_unary() {
         let tokens = vec![Token::Sub, Token::from(2), Token::EOF];
-        let expression = Parser::new(tokens).expr(0).unwrap();
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
 
-        let expected = Expression::Unary(Token::Sub, Box::new(Expression::from(2)));
+        let expected = Expression::Unary(Token::Sub, node(Expression::from(2)));
 
         assert_eq!(expected, expression);
     }
@@ -188,15 +517,15 @@ mod test {
             Token::from(2),
             Token::EOF,
         ];
-        let expression = Parser::new(tokens).expr(0).unwrap();
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
 
         let expected = Expression::Binary(
-            Box::new(Expression::from(3)),
+            node(Expression::from(3)),
             Token::Add,
-            Box::new(Expression::Binary(
-                Box::new(Expression::from(2)),
+            node(Expression::Binary(
+                node(Expression::from(2)),
                 Token::Mul,
-                Box::new(Expression::from(2)),
+                node(Expression::from(2)),
             )),
         );
 
@@ -213,16 +542,16 @@ mod test {
             Token::from(2),
             Token::EOF,
         ];
-        let expression = Parser::new(tokens).expr(0).unwrap();
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
 
         let expected = Expression::Binary(
-            Box::new(Expression::Binary(
-                Box::new(Expression::from(3)),
+            node(Expression::Binary(
+                node(Expression::from(3)),
                 Token::Add,
-                Box::new(Expression::from(2)),
+                node(Expression::from(2)),
             )),
             Token::Sub,
-            Box::new(Expression::from(2)),
+            node(Expression::from(2)),
         );
 
         assert_eq!(expected, expression);
@@ -236,12 +565,12 @@ mod test {
             Token::from(2),
         ];
 
-        let expression = Parser::new(tokens).expr(0).unwrap();
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
 
         let expected = Expression::Binary(
-            Box::new(Expression::Variable("a".to_string())),
+            node(Expression::Variable("a".to_string())),
             Token::ASSIGN,
-            Box::new(Expression::from(2)),
+            node(Expression::from(2)),
         );
 
         assert_eq!(expected, expression);
@@ -260,16 +589,579 @@ mod test {
             Token::from(0),
         ];
 
-        let expression = Parser::new(tokens).expr(0).unwrap();
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
 
         let expected = Expression::Conditional(
-            Box::new(Expression::Binary(
-                Box::new(Expression::from(1)),
+            node(Expression::Binary(
+                node(Expression::from(1)),
                 Token::LT,
-                Box::new(Expression::from(9)),
+                node(Expression::from(9)),
+            )),
+            node(Expression::from(1)),
+            node(Expression::from(0)),
+        );
+
+        assert_eq!(expected, expression);
+    }
+
+    #[test]
+    fn test_span() {
+        let tokens = crate::lexer::Lexer::new("1 + (2 * 3)").lex().unwrap();
+        let expression = Parser::new(tokens).expr(0).unwrap();
+
+        assert_eq!(Span::new(0, 11, 1, 1), expression.span);
+
+        match expression.node {
+            Expression::Binary(left, _, right) => {
+                assert_eq!(Span::new(0, 1, 1, 1), left.span);
+                assert_eq!(Span::new(4, 7, 1, 5), right.span);
+            }
+            _ => panic!("expected binary expression"),
+        }
+    }
+
+    #[test]
+    fn test_unmatched_paren() {
+        let tokens = crate::lexer::Lexer::new("(1 + 2").lex().unwrap();
+        let error = Parser::new(tokens).expr(0).unwrap_err();
+
+        assert_eq!(
+            HitungError::UnmatchedParen {
+                span: Span::new(0, 1, 1, 1)
+            },
+            error
+        );
+    }
+
+    #[test]
+    fn test_empty_paren() {
+        let tokens = crate::lexer::Lexer::new("a = 1\nb = ()").lex().unwrap();
+        let error = Parser::new(tokens).program().unwrap_err();
+
+        assert_eq!(
+            HitungError::UnexpectedEof {
+                span: Span::new(11, 1, 2, 6)
+            },
+            error
+        );
+    }
+
+    #[test]
+    fn test_assignment_binds_loosest() {
+        let tokens = vec![
+            Token::IDENTIFIER("a".to_string()),
+            Token::ASSIGN,
+            Token::IDENTIFIER("a".to_string()),
+            Token::Add,
+            Token::from(1),
+            Token::EOF,
+        ];
+
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
+
+        let expected = Expression::Binary(
+            node(Expression::Variable("a".to_string())),
+            Token::ASSIGN,
+            node(Expression::Binary(
+                node(Expression::Variable("a".to_string())),
+                Token::Add,
+                node(Expression::from(1)),
+            )),
+        );
+
+        assert_eq!(expected, expression);
+    }
+
+    #[test]
+    fn test_program() {
+        let tokens = crate::lexer::Lexer::new("a = 8\n\nb = a + 2 * 3; b\n")
+            .lex()
+            .unwrap();
+        let program = Parser::new(tokens).program().unwrap();
+
+        assert_eq!(3, program.statements.len());
+        assert_eq!(
+            Expression::Variable("b".to_string()),
+            program.statements[2].node
+        );
+    }
+
+    #[test]
+    fn test_program_empty() {
+        let tokens = crate::lexer::Lexer::new(" ;\n").lex().unwrap();
+        let program = Parser::new(tokens).program().unwrap();
+
+        assert_eq!(Program::default(), program);
+    }
+
+    #[test]
+    fn test_program_missing_separator() {
+        let tokens = crate::lexer::Lexer::new("1 2").lex().unwrap();
+        let error = Parser::new(tokens).program().unwrap_err();
+
+        let expected = HitungError::UnexpectedToken {
+            token: Token::from(2),
+            span: Span::new(2, 1, 1, 3),
+        };
+
+        assert_eq!(expected, error);
+    }
+
+    #[test]
+    fn test_paren_leftover_tokens() {
+        let error = parse("(1 2)").unwrap_err();
+        let expected = HitungError::UnexpectedToken {
+            token: Token::from(2),
+            span: Span::new(3, 1, 1, 4),
+        };
+        assert_eq!(expected, error);
+
+        let error = parse("(a = 1\nb = 2)").unwrap_err();
+        let expected = HitungError::UnexpectedToken {
+            token: Token::IDENTIFIER("b".to_string()),
+            span: Span::new(7, 1, 2, 1),
+        };
+        assert_eq!(expected, error);
+    }
+
+    fn parse(source: &str) -> Result<Spanned<Expression>> {
+        let tokens = crate::lexer::Lexer::new(source).lex()?;
+        Parser::new(tokens).with_source(source).expr(0)
+    }
+
+    #[test]
+    fn test_if_then_else_sub_expressions() {
+        let expression = parse("if a + 1 > b * 2 then x - 1 else (y)").unwrap();
+
+        match expression.node {
+            Expression::Conditional(condition, then, els) => {
+                assert!(matches!(
+                    condition.node,
+                    Expression::Binary(_, Token::GT, _)
+                ));
+                assert!(matches!(then.node, Expression::Binary(_, Token::Sub, _)));
+                assert!(matches!(els.node, Expression::Paren(_)));
+            }
+            _ => panic!("expected conditional"),
+        }
+    }
+
+    #[test]
+    fn test_if_then_else_nested() {
+        let expression = parse("if a then if b then 1 else 2 else 3").unwrap();
+
+        match expression.node {
+            Expression::Conditional(_, then, els) => {
+                assert!(matches!(then.node, Expression::Conditional(_, _, _)));
+                assert_eq!(Expression::from(3), els.node);
+            }
+            _ => panic!("expected conditional"),
+        }
+    }
+
+    #[test]
+    fn test_if_then_else_multiline() {
+        let expression = parse("if a\nthen 1\nelse\n  2").unwrap();
+
+        assert!(matches!(expression.node, Expression::Conditional(_, _, _)));
+    }
+
+    #[test]
+    fn test_if_missing_then() {
+        let error = parse("if 1 < 2 1 else 0").unwrap_err();
+
+        let expected = HitungError::ExpectedToken {
+            expected: Token::Then,
+            found: Token::from(1),
+            span: Span::new(9, 1, 1, 10),
+        };
+
+        assert_eq!(expected, error);
+    }
+
+    #[test]
+    fn test_if_missing_else() {
+        let error = parse("if 1 < 2 then 1").unwrap_err();
+
+        assert_eq!(
+            HitungError::ExpectedToken {
+                expected: Token::Else,
+                found: Token::EOF,
+                span: Span::new(15, 0, 1, 16),
+            },
+            error
+        );
+    }
+
+    #[test]
+    fn test_while() {
+        let expression = parse("selama i < 10 lakukan i = i + 1").unwrap();
+
+        match expression.node {
+            Expression::While(condition, body) => {
+                assert!(matches!(
+                    condition.node,
+                    Expression::Binary(_, Token::LT, _)
+                ));
+                assert!(matches!(body.node, Expression::Binary(_, Token::ASSIGN, _)));
+            }
+            _ => panic!("expected while"),
+        }
+        assert_eq!(Span::new(0, 31, 1, 1), expression.span);
+    }
+
+    #[test]
+    fn test_while_missing_do() {
+        let error = parse("selama i < 10 i = i + 1").unwrap_err();
+
+        let expected = HitungError::ExpectedToken {
+            expected: Token::Do,
+            found: Token::IDENTIFIER("i".to_string()),
+            span: Span::new(14, 1, 1, 15),
+        };
+
+        assert_eq!(expected, error);
+    }
+
+    #[test]
+    fn test_for() {
+        let tokens = vec![
+            Token::For,
+            Token::IDENTIFIER("i".to_string()),
+            Token::From,
+            Token::from(1),
+            Token::To,
+            Token::from(10),
+            Token::Do,
+            Token::IDENTIFIER("i".to_string()),
+        ];
+
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
+
+        let expected = Expression::For(
+            "i".to_string(),
+            node(Expression::from(1)),
+            node(Expression::from(10)),
+            None,
+            node(Expression::Variable("i".to_string())),
+        );
+
+        assert_eq!(expected, expression);
+    }
+
+    #[test]
+    fn test_for_step() {
+        let expression = parse("untuk i dari 10 sampai 1\nlangkah -3\nlakukan i").unwrap();
+
+        match expression.node {
+            Expression::For(name, _, _, Some(step), _) => {
+                assert_eq!("i", name);
+                assert!(matches!(step.node, Expression::Unary(Token::Sub, _)));
+            }
+            _ => panic!("expected for with step"),
+        }
+    }
+
+    #[test]
+    fn test_for_missing_to() {
+        let error = parse("untuk i dari 1 lakukan i").unwrap_err();
+
+        let expected = HitungError::ExpectedToken {
+            expected: Token::To,
+            found: Token::Do,
+            span: Span::new(15, 7, 1, 16),
+        };
+
+        assert_eq!(expected, error);
+    }
+
+    #[test]
+    fn test_comparison_below_arithmetic() {
+        let tokens = vec![
+            Token::from(1),
+            Token::Add,
+            Token::from(2),
+            Token::LTE,
+            Token::from(3),
+            Token::Mul,
+            Token::from(4),
+            Token::EOF,
+        ];
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
+
+        let expected = Expression::Binary(
+            node(Expression::Binary(
+                node(Expression::from(1)),
+                Token::Add,
+                node(Expression::from(2)),
+            )),
+            Token::LTE,
+            node(Expression::Binary(
+                node(Expression::from(3)),
+                Token::Mul,
+                node(Expression::from(4)),
+            )),
+        );
+
+        assert_eq!(expected, expression);
+    }
+
+    #[test]
+    fn test_unary_operand() {
+        let tokens = vec![
+            Token::Sub,
+            Token::IDENTIFIER("a".to_string()),
+            Token::NEQ,
+            Token::IDENTIFIER("b".to_string()),
+            Token::EOF,
+        ];
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
+
+        let expected = Expression::Binary(
+            node(Expression::Unary(
+                Token::Sub,
+                node(Expression::Variable("a".to_string())),
+            )),
+            Token::NEQ,
+            node(Expression::Variable("b".to_string())),
+        );
+
+        assert_eq!(expected, expression);
+    }
+
+    #[test]
+    fn test_logic_precedence() {
+        let tokens = vec![
+            Token::Not,
+            Token::IDENTIFIER("a".to_string()),
+            Token::LT,
+            Token::IDENTIFIER("b".to_string()),
+            Token::Or,
+            Token::IDENTIFIER("c".to_string()),
+            Token::And,
+            Token::IDENTIFIER("d".to_string()),
+            Token::EOF,
+        ];
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
+
+        let expected = Expression::Binary(
+            node(Expression::Unary(
+                Token::Not,
+                node(Expression::Binary(
+                    node(Expression::Variable("a".to_string())),
+                    Token::LT,
+                    node(Expression::Variable("b".to_string())),
+                )),
+            )),
+            Token::Or,
+            node(Expression::Binary(
+                node(Expression::Variable("c".to_string())),
+                Token::And,
+                node(Expression::Variable("d".to_string())),
+            )),
+        );
+
+        assert_eq!(expected, expression);
+    }
+
+    #[test]
+    fn test_function_definition() {
+        let tokens = crate::lexer::Lexer::new("fungsi luas(p, l) = p * l\nluas(3, 4)")
+            .lex()
+            .unwrap();
+        let program = Parser::new(tokens).program().unwrap();
+
+        let function = &program.functions[0].node;
+        assert_eq!("luas", function.name.node);
+        assert_eq!(
+            vec!["p", "l"],
+            function
+                .params
+                .iter()
+                .map(|param| param.node.as_str())
+                .collect::<Vec<_>>()
+        );
+        assert!(matches!(
+            function.body.node,
+            Expression::Binary(_, Token::Mul, _)
+        ));
+
+        match &program.statements[0].node {
+            Expression::Call(name, args) => {
+                assert_eq!("luas", name);
+                assert_eq!(2, args.len());
+            }
+            _ => panic!("expected call"),
+        }
+    }
+
+    #[test]
+    fn test_call() {
+        let tokens = vec![
+            Token::IDENTIFIER("f".to_string()),
+            Token::LParen,
+            Token::from(1),
+            Token::Add,
+            Token::from(2),
+            Token::Comma,
+            Token::IDENTIFIER("x".to_string()),
+            Token::RParen,
+            Token::Mul,
+            Token::from(2),
+            Token::EOF,
+        ];
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
+
+        let expected = Expression::Binary(
+            node(Expression::Call(
+                "f".to_string(),
+                vec![
+                    Spanned::from(Expression::Binary(
+                        node(Expression::from(1)),
+                        Token::Add,
+                        node(Expression::from(2)),
+                    )),
+                    Spanned::from(Expression::Variable("x".to_string())),
+                ],
+            )),
+            Token::Mul,
+            node(Expression::from(2)),
+        );
+
+        assert_eq!(expected, expression);
+    }
+
+    #[test]
+    fn test_call_unmatched_paren() {
+        let error = parse("f(1, 2").unwrap_err();
+
+        assert_eq!(
+            HitungError::UnmatchedParen {
+                span: Span::new(1, 1, 1, 2)
+            },
+            error
+        );
+    }
+
+    #[test]
+    fn test_function_missing_assign() {
+        let tokens = crate::lexer::Lexer::new("fungsi f(x) x").lex().unwrap();
+        let error = Parser::new(tokens).program().unwrap_err();
+
+        let expected = HitungError::ExpectedToken {
+            expected: Token::ASSIGN,
+            found: Token::IDENTIFIER("x".to_string()),
+            span: Span::new(12, 1, 1, 13),
+        };
+
+        assert_eq!(expected, error);
+    }
+
+    #[test]
+    fn test_reserved_keyword() {
+        let error = parse("dari = 1").unwrap_err();
+        assert_eq!(
+            HitungError::ReservedKeyword {
+                keyword: "dari".to_string(),
+                span: Span::new(0, 4, 1, 1),
+            },
+            error
+        );
+
+        let source = "fungsi f(x, jika) = x";
+        let tokens = crate::lexer::Lexer::new(source).lex().unwrap();
+        let error = Parser::new(tokens)
+            .with_source(source)
+            .program()
+            .unwrap_err();
+        assert_eq!(
+            HitungError::ReservedKeyword {
+                keyword: "jika".to_string(),
+                span: Span::new(12, 4, 1, 13),
+            },
+            error
+        );
+
+        // quoted the way it was typed, not as the English keyword
+        let error = parse("maka = 1").unwrap_err();
+        assert_eq!(
+            "`maka` is a reserved keyword",
+            error.message(Default::default(), Default::default())
+        );
+
+        let error = parse("untuk sampai dari 1 sampai 2 lakukan 0").unwrap_err();
+        assert_eq!(crate::error::ErrorKind::ReservedKeyword, error.kind());
+    }
+
+    #[test]
+    fn test_power_right_associative() {
+        let tokens = vec![
+            Token::from(2),
+            Token::Pow,
+            Token::from(3),
+            Token::Pow,
+            Token::from(2),
+        ];
+
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
+
+        let expected = Expression::Binary(
+            node(Expression::from(2)),
+            Token::Pow,
+            node(Expression::Binary(
+                node(Expression::from(3)),
+                Token::Pow,
+                node(Expression::from(2)),
+            )),
+        );
+
+        assert_eq!(expected, expression);
+    }
+
+    #[test]
+    fn test_power_above_unary_minus() {
+        let tokens = vec![Token::Sub, Token::from(2), Token::Pow, Token::from(2)];
+
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
+
+        let expected = Expression::Unary(
+            Token::Sub,
+            node(Expression::Binary(
+                node(Expression::from(2)),
+                Token::Pow,
+                node(Expression::from(2)),
+            )),
+        );
+
+        assert_eq!(expected, expression);
+    }
+
+    #[test]
+    fn test_remainder_and_floor_division() {
+        let tokens = vec![
+            Token::from(1),
+            Token::Add,
+            Token::from(7),
+            Token::Mod,
+            Token::from(4),
+            Token::FloorDiv,
+            Token::from(2),
+        ];
+
+        let expression = Parser::new(spanned(tokens)).expr(0).unwrap().node;
+
+        let expected = Expression::Binary(
+            node(Expression::from(1)),
+            Token::Add,
+            node(Expression::Binary(
+                node(Expression::Binary(
+                    node(Expression::from(7)),
+                    Token::Mod,
+                    node(Expression::from(4)),
+                )),
+                Token::FloorDiv,
+                node(Expression::from(2)),
             )),
-            Box::new(Expression::from(1)),
-            Box::new(Expression::from(0)),
         );
 
         assert_eq!(expected, expression);
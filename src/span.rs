@@ -0,0 +1,70 @@
+/// Location of a piece of source code.
+///
+/// `offset` and `len` are in bytes so a span can slice the original source,
+/// `line` and `column` start at 1 and are meant for humans.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub offset: usize,
+    pub len: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(offset: usize, len: usize, line: usize, column: usize) -> Self {
+        Span {
+            offset,
+            len,
+            line,
+            column,
+        }
+    }
+
+    pub fn end(&self) -> usize {
+        self.offset + self.len
+    }
+
+    /// Span starting at `self` and ending at the end of `other`.
+    pub fn to(self, other: Span) -> Span {
+        let end = self.end().max(other.end());
+
+        Span {
+            len: end - self.offset,
+            ..self
+        }
+    }
+}
+
+/// A value together with the place it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Spanned<T> {
+    pub node: T,
+    pub span: Span,
+}
+
+impl<T> Spanned<T> {
+    pub fn new(node: T, span: Span) -> Self {
+        Spanned { node, span }
+    }
+}
+
+impl<T> From<T> for Spanned<T> {
+    fn from(node: T) -> Self {
+        Spanned::new(node, Span::default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_span_to() {
+        let left = Span::new(0, 1, 1, 1);
+        let right = Span::new(4, 3, 1, 5);
+
+        let expected = Span::new(0, 7, 1, 1);
+
+        assert_eq!(expected, left.to(right));
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum Token {
     LParen,
@@ -6,17 +8,37 @@ pub enum Token {
     Sub,
     Mul,
     Div,
+    FloorDiv,
+    Mod,
+    Pow,
     Num(f64),
     EOF,
-    ILLEGAL,
     ASSIGN,
     IDENTIFIER(String),
     If,
     Then,
     Else,
     EQ,
+    NEQ,
     LT,
+    LTE,
     GT,
+    GTE,
+    And,
+    Or,
+    Not,
+    Function,
+    While,
+    For,
+    From,
+    To,
+    Step,
+    Do,
+    Comma,
+    Newline,
+    Semicolon,
+    /// Only produced when the lexer keeps comments, the parser never sees it.
+    Comment(String),
 }
 
 impl From<i32> for Token {
@@ -26,21 +48,94 @@ impl From<i32> for Token {
 }
 
 impl Token {
+    /// Words that cannot be used as a name.
+    pub fn is_keyword(&self) -> bool {
+        matches!(
+            self,
+            Token::If
+                | Token::Then
+                | Token::Else
+                | Token::And
+                | Token::Or
+                | Token::Not
+                | Token::Function
+                | Token::While
+                | Token::For
+                | Token::From
+                | Token::To
+                | Token::Step
+                | Token::Do
+        )
+    }
+
+    /// Newlines and `;` end a statement.
+    pub fn is_separator(&self) -> bool {
+        matches!(self, Token::Newline | Token::Semicolon)
+    }
+
     //Left Binding Power
     pub fn lbp(&self) -> usize {
         match *self {
+            Token::Or => 3,
+            Token::And => 4,
+            Token::EQ | Token::NEQ | Token::LT | Token::LTE | Token::GT | Token::GTE => 5,
             Token::Add => 10,
             Token::Sub => 10,
             Token::Mul => 20,
             Token::Div => 20,
+            Token::FloorDiv | Token::Mod => 20,
+            Token::Pow => 30,
             Token::LParen => 99,
-            Token::ASSIGN => 100,
+            Token::ASSIGN => 1,
             Token::RParen => 0,
             _ => 0,
         }
     }
 }
 
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::LParen => write!(f, "("),
+            Token::RParen => write!(f, ")"),
+            Token::Add => write!(f, "+"),
+            Token::Sub => write!(f, "-"),
+            Token::Mul => write!(f, "*"),
+            Token::Div => write!(f, "/"),
+            Token::FloorDiv => write!(f, "//"),
+            Token::Mod => write!(f, "%"),
+            Token::Pow => write!(f, "^"),
+            Token::Num(n) => write!(f, "{}", n),
+            Token::EOF => write!(f, "end of input"),
+            Token::ASSIGN => write!(f, "="),
+            Token::IDENTIFIER(name) => write!(f, "{}", name),
+            Token::If => write!(f, "if"),
+            Token::Then => write!(f, "then"),
+            Token::Else => write!(f, "else"),
+            Token::EQ => write!(f, "=="),
+            Token::NEQ => write!(f, "!="),
+            Token::LT => write!(f, "<"),
+            Token::LTE => write!(f, "<="),
+            Token::GT => write!(f, ">"),
+            Token::GTE => write!(f, ">="),
+            Token::And => write!(f, "and"),
+            Token::Or => write!(f, "or"),
+            Token::Not => write!(f, "not"),
+            Token::Function => write!(f, "fungsi"),
+            Token::While => write!(f, "selama"),
+            Token::For => write!(f, "untuk"),
+            Token::From => write!(f, "dari"),
+            Token::To => write!(f, "sampai"),
+            Token::Step => write!(f, "langkah"),
+            Token::Do => write!(f, "lakukan"),
+            Token::Comma => write!(f, ","),
+            Token::Newline => write!(f, "newline"),
+            Token::Semicolon => write!(f, ";"),
+            Token::Comment(text) => write!(f, "{}", text),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -96,8 +191,36 @@ mod test {
     #[test]
     fn test_token_assignment() {
         let actual = Token::ASSIGN.lbp();
-        let expected: usize = 100;
+        let expected: usize = 1;
 
         assert_eq!(expected, actual);
     }
+
+    #[test]
+    fn test_token_comparison() {
+        for token in &[
+            Token::EQ,
+            Token::NEQ,
+            Token::LT,
+            Token::LTE,
+            Token::GT,
+            Token::GTE,
+        ] {
+            assert_eq!(5, token.lbp());
+        }
+    }
+
+    #[test]
+    fn test_token_logic() {
+        assert_eq!(3, Token::Or.lbp());
+        assert_eq!(4, Token::And.lbp());
+        assert_eq!(0, Token::Not.lbp());
+    }
+
+    #[test]
+    fn test_token_power() {
+        assert_eq!(20, Token::Mod.lbp());
+        assert_eq!(20, Token::FloorDiv.lbp());
+        assert!(Token::Pow.lbp() > Token::Mul.lbp());
+    }
 }
@@ -0,0 +1,112 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn hitung(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_hitung"))
+        .args(args)
+        .env("LANG", "en_US.UTF-8")
+        .output()
+        .expect("Error when run hitung")
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn test_expression() {
+    let output = hitung(&["-e", "2 + 5 * 3"]);
+
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("17\n", stdout(&output));
+    assert_eq!("", stderr(&output));
+}
+
+#[test]
+fn test_file() {
+    let path = env::temp_dir().join(format!("hitung-cli-{}.htg", std::process::id()));
+    fs::write(&path, "a = 8\nb = a * 2\nb + 1\n").unwrap();
+
+    let output = hitung(&[path.to_str().unwrap()]);
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("17\n", stdout(&output));
+}
+
+#[test]
+fn test_missing_file() {
+    let output = hitung(&["tidak-ada.htg"]);
+
+    assert_eq!(Some(1), output.status.code());
+    assert!(stderr(&output).contains("tidak-ada.htg"));
+}
+
+#[test]
+fn test_stdin() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_hitung"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Error when run hitung");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"a = 8; a * 2\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("16\n", stdout(&output));
+}
+
+#[test]
+fn test_echo() {
+    let output = hitung(&["--echo", "-e", "a = 1; a + 2\na * 10"]);
+
+    assert_eq!(Some(0), output.status.code());
+    assert_eq!("1\n3\n10\n", stdout(&output));
+}
+
+#[test]
+fn test_error_exit_status() {
+    let output = hitung(&["-e", "a = 1\nb + 1"]);
+
+    assert_eq!(Some(1), output.status.code());
+    assert_eq!("", stdout(&output));
+    assert!(stderr(&output).contains("error: variable `b` is not declared"));
+    assert!(stderr(&output).contains("<expr>:2:1"));
+}
+
+#[test]
+fn test_language() {
+    let output = hitung(&["--lang", "id", "-e", "b"]);
+
+    assert_eq!(Some(1), output.status.code());
+    assert!(stderr(&output).contains("galat: variabel `b` belum dideklarasikan"));
+}
+
+#[test]
+fn test_usage_error() {
+    let output = hitung(&["--jumlah"]);
+
+    assert_eq!(Some(2), output.status.code());
+    assert!(stderr(&output).contains("usage: hitung"));
+}
+
+#[test]
+fn test_usage_error_indonesian() {
+    let output = hitung(&["--lang", "id", "--jumlah"]);
+
+    assert_eq!(Some(2), output.status.code());
+    assert!(stderr(&output).contains("galat: opsi `--jumlah` tidak dikenal"));
+    assert!(stderr(&output).contains("pemakaian: hitung"));
+}